@@ -0,0 +1,59 @@
+use super::assert_admin;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_memory::sol_memcpy;
+
+#[derive(Accounts)]
+pub struct InitializeAdmin<'info> {
+    #[account(
+      init,
+      payer = admin,
+      space = 40,
+      seeds = [
+        b"admin".as_ref(),
+      ],
+      bump
+    )]
+    /// CHECK: Admin account
+    pub admin_record: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_admin(ctx: Context<InitializeAdmin>) -> Result<()> {
+    let admin_record = &mut ctx.accounts.admin_record.to_account_info();
+    sol_memcpy(
+        &mut admin_record.data.borrow_mut(),
+        ctx.accounts.admin.key.as_ref(),
+        32,
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ChangeAdmin<'info> {
+    #[account(
+      mut,
+      seeds = [
+        b"admin".as_ref(),
+      ],
+      bump
+    )]
+    /// CHECK: Admin account
+    pub admin_record: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub new_admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn change_admin(ctx: Context<ChangeAdmin>) -> Result<()> {
+    assert_admin(&ctx.accounts.admin_record, &ctx.accounts.admin)?;
+    let admin_record = &mut ctx.accounts.admin_record.to_account_info();
+    sol_memcpy(
+        &mut admin_record.data.borrow_mut(),
+        ctx.accounts.new_admin.key.as_ref(),
+        32,
+    );
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use crate::{events::PoolAudited, math::SafeMath, state::Pool};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct AuditPool<'info> {
+    pub pool: Account<'info, Pool>,
+    #[account(
+        associated_token::mint = pool.collateral_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_collateral_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Reconciles the vault against the pool's books. Anyone can run it, and a mismatch is reported
+/// rather than rejected so indexers can pick up drift or donations.
+pub fn audit_pool(ctx: Context<AuditPool>) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+    let vault_amount = ctx.accounts.pool_collateral_token_account.amount;
+    let booked = pool
+        .total_principal
+        .safe_add(pool.collateral_amount)?
+        .safe_add(pool.fee_amount)?;
+    emit!(PoolAudited {
+        pool: pool.key(),
+        vault_amount,
+        total_principal: pool.total_principal,
+        collateral_amount: pool.collateral_amount,
+        fee_amount: pool.fee_amount,
+        balanced: vault_amount == booked,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
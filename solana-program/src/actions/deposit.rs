@@ -1,14 +1,19 @@
 use crate::{
     error::ChickenError,
-    state::{Pool, UserPosition},
+    math::SafeMath,
+    state::{PlayerProfile, Pool, UserPosition},
 };
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    token_2022::TransferChecked,
+    associated_token::AssociatedToken,
+    token_2022::{Token2022, TransferChecked},
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
-use super::{assert_pool_active, bps, update_pool_state};
+use super::{
+    assert_pool_active, bps, bps_up, current_phase, extend_pool_timer, mint_position_receipt,
+    missed_check_in, record_deposit, update_pool_state,
+};
 
 #[derive(Accounts)]
 #[instruction(amount: u64)]
@@ -43,28 +48,77 @@ pub struct Deposit<'info> {
       bump
     )]
     pub user_position: Account<'info, UserPosition>,
+    #[account(
+      init_if_needed,
+      payer = payer,
+      space = 8 + std::mem::size_of::<PlayerProfile>(),
+      seeds = [
+        b"player_profile".as_ref(),
+        user.key().as_ref(),
+      ],
+      bump
+    )]
+    pub player_profile: Account<'info, PlayerProfile>,
     pub collateral_mint: InterfaceAccount<'info, Mint>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
+    #[account(
+      init,
+      payer = payer,
+      seeds = [
+        b"receipt".as_ref(),
+        user_position.key().as_ref(),
+      ],
+      bump,
+      mint::decimals = 0,
+      mint::authority = pool,
+      mint::token_program = receipt_token_program,
+      extensions::close_authority::authority = pool,
+      extensions::permanent_delegate::delegate = pool,
+      extensions::metadata_pointer::authority = pool,
+      extensions::metadata_pointer::metadata_address = receipt_mint,
+    )]
+    pub receipt_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    // a settled position's emptied receipt account is left with the holder and reused on rejoin
+    #[account(
+      init_if_needed,
+      payer = payer,
+      associated_token::mint = receipt_mint,
+      associated_token::authority = user,
+      associated_token::token_program = receipt_token_program
+    )]
+    pub user_receipt_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub receipt_token_program: Option<Program<'info, Token2022>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
 }
 
-pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+/// `max_fee_bps` and `max_collateral_bps` bound the rates the caller quoted against.
+pub fn deposit(
+    ctx: Context<Deposit>,
+    amount: u64,
+    max_fee_bps: u16,
+    max_collateral_bps: u16,
+) -> Result<()> {
     let current_slot = Clock::get()?.slot;
+    let pool_info = ctx.accounts.pool.to_account_info();
     let pool = &mut ctx.accounts.pool;
     let token_account_amount = ctx.accounts.pool_collateral_token_account.amount;
     update_pool_state(pool, current_slot)?;
-    assert_pool_active(pool)?;
-    let user_position = &mut ctx.accounts.user_position;
-    if let Some(tdl) = pool.total_deposit_limit {
-        if token_account_amount + amount > tdl {
-            return Err(ChickenError::PoolDepositLimitExceeded.into());
-        }
+    if pool.share_mint.is_some() {
+        return err!(ChickenError::SharePool);
     }
-    if let Some(deposit_limit) = pool.max_deposit {
-        if user_position.deposit_amount + amount > deposit_limit {
-            return Err(ChickenError::UserDepositLimitExceeded.into());
-        }
+    let user_position = &mut ctx.accounts.user_position;
+    check_deposit(
+        pool,
+        user_position,
+        amount,
+        token_account_amount,
+        current_slot,
+    )?;
+    if pool.deposit_fee_bps > max_fee_bps || pool.collateral_bps > max_collateral_bps {
+        return err!(ChickenError::FeeLimitExceeded);
     }
+    let new_position = user_position.deposit_time == 0;
     anchor_spl::token_interface::transfer_checked(
         CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
@@ -79,17 +133,124 @@ pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
         ctx.accounts.collateral_mint.decimals,
     )?;
 
-    pool.users += 1;
-    let fee = bps(amount, pool.deposit_fee_bps)?;
-    let collateral = bps(amount - fee, pool.collateral_bps)?;
-    pool.fee_amount += fee;
-    pool.collateral_amount += collateral;
-    if user_position.deposit_time == 0 {
+    let (fee, _) = book_deposit(pool, user_position, amount)?;
+    record_deposit(
+        &mut ctx.accounts.player_profile,
+        ctx.accounts.user.key(),
+        ctx.bumps.player_profile,
+        amount,
+        fee,
+        new_position,
+    );
+    if new_position {
         user_position.deposit_time = current_slot;
+        user_position.payer = ctx.accounts.payer.key();
     }
     user_position.owner = ctx.accounts.user.key();
     user_position.pool = pool.key();
-    user_position.collateral_amount += collateral;
-    user_position.deposit_amount += amount - fee - collateral;
+    // depositing counts as a heartbeat
+    user_position.last_check_in = current_slot;
+    extend_pool_timer(pool, user_position.owner, amount);
+
+    // the receipt is minted once, top-ups leave the receipt accounts out
+    if pool.position_receipts && new_position {
+        let (Some(receipt_mint), Some(user_receipt_token_account), Some(receipt_token_program)) = (
+            &ctx.accounts.receipt_mint,
+            &ctx.accounts.user_receipt_token_account,
+            &ctx.accounts.receipt_token_program,
+        ) else {
+            return err!(ChickenError::PositionReceiptRequired);
+        };
+        user_position.receipt_mint = Some(receipt_mint.key());
+        mint_position_receipt(
+            pool_info,
+            &[
+                b"pool".as_ref(),
+                pool.pool_id.as_ref(),
+                pool.creator.as_ref(),
+                &[pool.bump],
+            ],
+            receipt_mint,
+            user_receipt_token_account,
+            ctx.accounts.payer.to_account_info(),
+            receipt_token_program,
+            &ctx.accounts.system_program,
+        )?;
+    }
+    Ok(())
+}
+
+/// Everything a deposit of `amount` into `user_position` has to pass, shared with
+/// `quote_deposit` and `deposit_shares`. `vault_amount` is the pool vault balance before the
+/// deposit.
+pub fn check_deposit(
+    pool: &Pool,
+    user_position: &UserPosition,
+    amount: u64,
+    vault_amount: u64,
+    current_slot: u64,
+) -> Result<()> {
+    assert_pool_active(pool)?;
+    if current_phase(pool, current_slot).is_some_and(|phase| !phase.deposit_allowed) {
+        return err!(ChickenError::DepositsClosed);
+    }
+    if pool
+        .sudden_death
+        .is_some_and(|sudden_death| current_slot >= sudden_death.earliest_end)
+    {
+        return err!(ChickenError::PoolEnded);
+    }
+    if pool.winner_count > 0 {
+        return err!(ChickenError::WinnersDecided);
+    }
+    if user_position.eliminated {
+        return err!(ChickenError::PositionEliminated);
+    }
+    // a top-up counts as a heartbeat, but not one that can cover up a missed check-in
+    if user_position.deposit_time != 0 && missed_check_in(pool, user_position, current_slot) {
+        return err!(ChickenError::MissedCheckIn);
+    }
+    if let Some(tdl) = pool.total_deposit_limit {
+        if vault_amount.safe_add(amount)? > tdl {
+            return Err(ChickenError::PoolDepositLimitExceeded.into());
+        }
+    }
+    if let Some(deposit_limit) = pool.max_deposit {
+        if user_position.deposit_amount.safe_add(amount)? > deposit_limit {
+            return Err(ChickenError::UserDepositLimitExceeded.into());
+        }
+    }
+    if let Some(max_users) = pool.max_users {
+        if user_position.deposit_time == 0 && pool.users >= max_users {
+            return Err(ChickenError::PoolFull.into());
+        }
+    }
     Ok(())
 }
+
+/// Splits `amount` into fee, collateral and principal and books them on the pool and position.
+/// Returns the fee and collateral taken.
+pub fn book_deposit(
+    pool: &mut Pool,
+    user_position: &mut UserPosition,
+    amount: u64,
+) -> Result<(u64, u64)> {
+    let fee = bps_up(amount, pool.deposit_fee_bps)?;
+    let collateral = bps(amount.safe_sub(fee)?, pool.collateral_bps)?;
+    let principal = amount.safe_sub(fee)?.safe_sub(collateral)?;
+    if user_position.deposit_time == 0 {
+        pool.users = pool.users.safe_add(1)?;
+        pool.joined = pool.joined.safe_add(1)?;
+    }
+    pool.fee_amount = pool.fee_amount.safe_add(fee)?;
+    pool.collateral_amount = pool.collateral_amount.safe_add(collateral)?;
+    if let Some(sudden_death) = pool.sudden_death.as_mut() {
+        sudden_death.at_risk_collateral = sudden_death.at_risk_collateral.safe_add(collateral)?;
+    }
+    pool.open_collateral = pool.open_collateral.safe_add(collateral)?;
+    pool.total_principal = pool.total_principal.safe_add(principal)?;
+    user_position.collateral_amount = user_position.collateral_amount.safe_add(collateral)?;
+    user_position.deposit_amount = user_position.deposit_amount.safe_add(principal)?;
+    user_position.deposit_fee = user_position.deposit_fee.safe_add(fee)?;
+    Ok((fee, collateral))
+}
@@ -0,0 +1,207 @@
+use super::{
+    assert_pool_withdrawable, pay_slashed_bond, process_withdraw, record_exit, update_pool_state,
+};
+use crate::{
+    error::ChickenError,
+    state::{ExitCondition, ExitOrder, PlayerProfile, Pool, PoolState, UserPosition},
+};
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    token_2022::TransferChecked,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[derive(Accounts)]
+pub struct PlaceExitOrder<'info> {
+    pub pool: Account<'info, Pool>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut,
+        seeds = [
+            b"user_position".as_ref(),
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+        constraint = user_position.owner == owner.key(),
+        constraint = user_position.pool == pool.key(),
+        constraint = user_position.receipt_mint.is_none() @ ChickenError::InvalidPositionReceipt,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<ExitOrder>(),
+        seeds = [
+            b"exit_order".as_ref(),
+            user_position.key().as_ref(),
+        ],
+        bump
+    )]
+    pub exit_order: Account<'info, ExitOrder>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn place_exit_order(
+    ctx: Context<PlaceExitOrder>,
+    condition: ExitCondition,
+    tip: u64,
+) -> Result<()> {
+    let exit_order = &mut ctx.accounts.exit_order;
+    exit_order.owner = ctx.accounts.owner.key();
+    exit_order.user_position = ctx.accounts.user_position.key();
+    exit_order.condition = condition;
+    exit_order.tip = tip;
+    ctx.accounts.user_position.has_exit_order = true;
+    if tip > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: exit_order.to_account_info(),
+                },
+            ),
+            tip,
+        )?;
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelExitOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut,
+        constraint = exit_order.owner == owner.key(),
+        close = owner
+    )]
+    pub exit_order: Account<'info, ExitOrder>,
+}
+
+pub fn cancel_exit_order(_ctx: Context<CancelExitOrder>) -> Result<()> {
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExecuteExitOrder<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    /// CHECK: Position owner, receives the payout and the exit order rent
+    #[account(mut, address = user_position.owner)]
+    pub owner: UncheckedAccount<'info>,
+    #[account(mut,
+        associated_token::mint = pool.collateral_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_collateral_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut,
+        token::mint = pool.collateral_mint,
+        token::authority = owner,
+        token::token_program = token_program
+    )]
+    pub owner_collateral_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut,
+        seeds = [
+            b"user_position".as_ref(),
+            pool.key().as_ref(),
+            user_position.owner.as_ref(),
+        ],
+        bump,
+        constraint = user_position.pool == pool.key(),
+        close = rent_receiver
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(mut,
+        seeds = [
+            b"exit_order".as_ref(),
+            user_position.key().as_ref(),
+        ],
+        bump,
+        close = owner
+    )]
+    pub exit_order: Account<'info, ExitOrder>,
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        space = 8 + std::mem::size_of::<PlayerProfile>(),
+        seeds = [
+            b"player_profile".as_ref(),
+            user_position.owner.as_ref(),
+        ],
+        bump
+    )]
+    pub player_profile: Account<'info, PlayerProfile>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: whoever funded the position gets its rent back
+    #[account(mut, address = user_position.payer)]
+    pub rent_receiver: UncheckedAccount<'info>,
+}
+
+pub fn execute_exit_order(ctx: Context<ExecuteExitOrder>) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
+    let pool_info = ctx.accounts.pool.to_account_info();
+    let pool = &mut ctx.accounts.pool;
+    update_pool_state(pool, current_slot)?;
+    assert_pool_withdrawable(pool)?;
+    // orders outlive the position they were placed on, a re-opened position starts without one
+    if !ctx.accounts.user_position.has_exit_order {
+        return err!(ChickenError::StaleExitOrder);
+    }
+
+    let exit_order = &ctx.accounts.exit_order;
+    let condition_met = match exit_order.condition {
+        ExitCondition::UsersAtMost(users) => pool.users <= users,
+        ExitCondition::CollateralAtLeast(amount) => pool.collateral_amount >= amount,
+        ExitCondition::SlotAtLeast(slot) => current_slot >= slot,
+    };
+    if !condition_met {
+        return err!(ChickenError::ExitConditionNotMet);
+    }
+
+    let user_position = &ctx.accounts.user_position;
+    let (final_amount, fee) = process_withdraw(pool, user_position, current_slot)?;
+    pay_slashed_bond(pool, user_position)?;
+    record_exit(
+        &mut ctx.accounts.player_profile,
+        ctx.bumps.player_profile,
+        user_position,
+        final_amount,
+        fee,
+        current_slot,
+        pool.state == PoolState::Refunding,
+    );
+    anchor_spl::token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.pool_collateral_token_account.to_account_info(),
+                to: ctx
+                    .accounts
+                    .owner_collateral_token_account
+                    .to_account_info(),
+                authority: pool_info,
+                mint: ctx.accounts.collateral_mint.to_account_info(),
+            },
+            &[&[
+                b"pool".as_ref(),
+                pool.pool_id.as_ref(),
+                pool.creator.as_ref(),
+                &[pool.bump],
+            ]],
+        ),
+        final_amount,
+        ctx.accounts.collateral_mint.decimals,
+    )?;
+
+    if exit_order.tip > 0 {
+        exit_order.sub_lamports(exit_order.tip)?;
+        ctx.accounts.keeper.add_lamports(exit_order.tip)?;
+    }
+    Ok(())
+}
@@ -1,9 +1,10 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, system_program};
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 use crate::{
-    state::{Pool, PoolMode, PoolState},
-    DEPOSIT_FEE_BPS, WITHDRAW_FEE_BPS,
+    error::ChickenError,
+    state::{PayoutCurve, Pool, PoolMode, PoolPhase, PoolState, SuddenDeath},
+    DEPOSIT_FEE_BPS, MAX_KEEPER_BOUNTY_BPS, MAX_POOL_PHASES, WITHDRAW_FEE_BPS,
 };
 
 #[derive(Accounts)]
@@ -31,6 +32,19 @@ pub struct InitializePool<'info> {
     pub pool_collateral_token_account: InterfaceAccount<'info, TokenAccount>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
+    #[account(
+        init,
+        payer = creator,
+        seeds = [
+            b"share_mint".as_ref(),
+            pool.key().as_ref(),
+        ],
+        bump,
+        mint::decimals = pool_collateral_mint.decimals,
+        mint::authority = pool,
+        mint::token_program = token_program
+    )]
+    pub share_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
@@ -43,10 +57,161 @@ pub struct InitializePoolArgs {
     pub minimum_deposit: u64,
     pub total_deposit_limit: Option<u64>,
     pub max_deposit: Option<u64>,
+    pub max_users: Option<u32>,
+    pub keeper_bounty_bps: u16,
+    pub position_receipts: bool,
+    pub share_tokens: bool,
+    pub transferable: bool,
+    pub sudden_death: Option<SuddenDeathArgs>,
+    pub phases: Vec<PoolPhase>,
+    pub payout_curve: PayoutCurve,
+    pub min_users: u32,
+}
+
+/// `commitment` is `sha256(secret || salt)`; `bond` lamports are returned on an honest reveal and
+/// shared among the winners otherwise.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct SuddenDeathArgs {
+    pub commitment: [u8; 32],
+    pub earliest_end: u64,
+    pub bond: u64,
+}
+
+impl InitializePoolArgs {
+    /// Rejects pools that could never run as configured, given the slot they are created in.
+    pub fn validate(&self, current_slot: u64) -> Result<()> {
+        if self.start_time >= self.end_time {
+            return err!(ChickenError::InvalidPoolDuration);
+        }
+        if self.start_time < current_slot {
+            return err!(ChickenError::PoolStartInPast);
+        }
+        if self.collateral_bps > 10_000 {
+            return err!(ChickenError::InvalidCollateralBps);
+        }
+        // the bounty comes out of protocol fees, so the creator only gets to pick it within a cap
+        if self.keeper_bounty_bps > MAX_KEEPER_BOUNTY_BPS {
+            return err!(ChickenError::InvalidKeeperBounty);
+        }
+        if self
+            .max_deposit
+            .is_some_and(|max_deposit| max_deposit < self.minimum_deposit)
+        {
+            return err!(ChickenError::MaxDepositBelowMinimum);
+        }
+        if self
+            .total_deposit_limit
+            .is_some_and(|limit| limit < self.minimum_deposit)
+        {
+            return err!(ChickenError::DepositLimitBelowMinimum);
+        }
+        if self
+            .max_users
+            .is_some_and(|max_users| max_users == 0 || max_users < self.min_users)
+        {
+            return err!(ChickenError::InvalidUserLimits);
+        }
+        if self.share_tokens
+            && (self.pool_mode != PoolMode::LastOutWinner
+                || self.position_receipts
+                || self.transferable
+                || self.max_users.is_some()
+                || self.min_users > 0)
+        {
+            return err!(ChickenError::SharesUnsupported);
+        }
+        match self.pool_mode {
+            PoolMode::KingOfTheHill {
+                max_end_time,
+                winner_share_bps,
+                ..
+            } if max_end_time < self.end_time
+                || winner_share_bps == 0
+                || winner_share_bps > 10_000 =>
+            {
+                return err!(ChickenError::InvalidKingOfTheHill);
+            }
+            PoolMode::LastNOut { winners: 0, .. } => {
+                return err!(ChickenError::InvalidLastNOut);
+            }
+            PoolMode::Survival { interval: 0 } => {
+                return err!(ChickenError::InvalidSurvivalInterval);
+            }
+            // check-ins are signed by the position owner, who may no longer hold the receipt
+            PoolMode::Survival { .. } if self.position_receipts => {
+                return err!(ChickenError::ReceiptsUnsupported);
+            }
+            _ => {}
+        }
+        if let Some(sudden_death) = &self.sudden_death {
+            if self.pool_mode != PoolMode::LastOutWinner
+                || self.position_receipts
+                || self.share_tokens
+                || sudden_death.earliest_end < self.start_time
+                || sudden_death.earliest_end > self.end_time
+            {
+                return err!(ChickenError::InvalidSuddenDeath);
+            }
+        }
+        // phases replace the last-out loser rule, so they only make sense on that mode
+        if !self.phases.is_empty()
+            && (self.pool_mode != PoolMode::LastOutWinner
+                || self.share_tokens
+                || self.sudden_death.is_some()
+                || self.phases.len() > MAX_POOL_PHASES
+                || self.phases[0].slot_offset != 0
+                || self
+                    .phases
+                    .windows(2)
+                    .any(|pair| pair[0].slot_offset >= pair[1].slot_offset)
+                || self
+                    .phases
+                    .iter()
+                    .any(|phase| phase.exit_penalty_bps > 10_000))
+        {
+            return err!(ChickenError::InvalidPoolPhases);
+        }
+        // only time-based pools vest along a curve
+        if !self.payout_curve.is_valid()
+            || self.pool_mode != PoolMode::TimeBased && self.payout_curve != PayoutCurve::Linear
+        {
+            return err!(ChickenError::InvalidPayoutCurve);
+        }
+        Ok(())
+    }
 }
 
 pub fn initialize_pool(ctx: Context<InitializePool>, args: InitializePoolArgs) -> Result<()> {
+    args.validate(Clock::get()?.slot)?;
     let pool = &mut ctx.accounts.pool;
+    if args.share_tokens {
+        let Some(share_mint) = &ctx.accounts.share_mint else {
+            return err!(ChickenError::ShareMintRequired);
+        };
+        pool.share_mint = Some(share_mint.key());
+    }
+    if let Some(sudden_death) = &args.sudden_death {
+        if sudden_death.bond > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.creator.to_account_info(),
+                        to: pool.to_account_info(),
+                    },
+                ),
+                sudden_death.bond,
+            )?;
+        }
+        pool.sudden_death = Some(SuddenDeath {
+            commitment: sudden_death.commitment,
+            earliest_end: sudden_death.earliest_end,
+            bond: sudden_death.bond,
+            ..Default::default()
+        });
+    }
+    pool.phase_count = args.phases.len() as u8;
+    pool.phases[..args.phases.len()].copy_from_slice(&args.phases);
     pool.state = PoolState::Pending;
     pool.creator = ctx.accounts.creator.key();
     pool.bump = ctx.bumps.pool;
@@ -63,5 +228,11 @@ pub fn initialize_pool(ctx: Context<InitializePool>, args: InitializePoolArgs) -
     pool.withdrawn = 0;
     pool.total_deposit_limit = args.total_deposit_limit;
     pool.max_deposit = args.max_deposit;
+    pool.max_users = args.max_users;
+    pool.keeper_bounty_bps = args.keeper_bounty_bps;
+    pool.position_receipts = args.position_receipts;
+    pool.transferable = args.transferable;
+    pool.payout_curve = args.payout_curve;
+    pool.min_users = args.min_users;
     Ok(())
 }
@@ -1,24 +1,64 @@
-mod claim_fees;
+mod admin;
+mod audit_pool;
 mod deposit;
+mod exit_order;
 mod init;
+mod player_profile;
+mod poke_pool;
+mod quote;
+mod receipt;
 mod remove_pool;
+mod season;
+mod set_position_delegate;
+mod settle;
+mod shares;
+mod sudden_death;
+mod survival;
+mod sweep_dust;
+mod transfer_position;
+mod treasury;
 mod withdraw;
 
 use crate::{
     error::ChickenError,
-    state::{Pool, PoolState},
+    math::SafeMath,
+    state::{Pool, PoolMode, PoolPhase, PoolState},
 };
-use anchor_lang::{error::ErrorCode, prelude::Result};
+use anchor_lang::prelude::{err, Pubkey, Result, Signer, UncheckedAccount};
 
-pub use claim_fees::*;
+pub use admin::*;
+pub use audit_pool::*;
 pub use deposit::*;
+pub use exit_order::*;
 pub use init::*;
+pub use player_profile::*;
+pub use poke_pool::*;
+pub use quote::*;
+pub use receipt::*;
 pub use remove_pool::*;
+pub use season::*;
+pub use set_position_delegate::*;
+pub use settle::*;
+pub use shares::*;
+pub use sudden_death::*;
+pub use survival::*;
+pub use sweep_dust::*;
+pub use transfer_position::*;
+pub use treasury::*;
 pub use withdraw::*;
 
 #[inline(always)]
 pub fn update_pool_state(pool: &mut Pool, current_slot: u64) -> Result<()> {
-    if pool.state == PoolState::Removed {
+    if pool.state == PoolState::Removed || pool.state == PoolState::Refunding {
+        return Ok(());
+    }
+    // too few players joined, so the game is called off and everyone is made whole. Players who
+    // already left still count as joined, so exits can't call off a game that was played.
+    if current_slot >= pool.start_time
+        && current_slot > join_deadline(pool)
+        && pool.joined < pool.min_users
+    {
+        pool.state = PoolState::Refunding;
         return Ok(());
     }
     if current_slot >= pool.start_time && current_slot <= pool.end_time {
@@ -30,6 +70,72 @@ pub fn update_pool_state(pool: &mut Pool, current_slot: u64) -> Result<()> {
     Ok(())
 }
 
+/// Whether a pool is over and holds no positions that could still be refunded, so its fees and
+/// leftovers can be cleaned up.
+#[inline(always)]
+pub fn pool_settled(pool: &Pool) -> bool {
+    match pool.state {
+        PoolState::Ended => true,
+        PoolState::Refunding => pool.users == 0,
+        _ => false,
+    }
+}
+
+/// Last slot a deposit can land in: the first closed phase, the sudden-death window or the end.
+#[inline(always)]
+pub fn join_deadline(pool: &Pool) -> u64 {
+    if let Some(phase) = pool.phases[..pool.phase_count as usize]
+        .iter()
+        .find(|phase| !phase.deposit_allowed)
+    {
+        return pool
+            .start_time
+            .saturating_add(phase.slot_offset)
+            .saturating_sub(1);
+    }
+    if let Some(sudden_death) = pool.sudden_death {
+        return sudden_death.earliest_end.saturating_sub(1);
+    }
+    pool.end_time
+}
+
+/// The phase in effect at `current_slot`, if the pool is phased and has started.
+#[inline(always)]
+pub fn current_phase(pool: &Pool, current_slot: u64) -> Option<PoolPhase> {
+    let elapsed = current_slot.checked_sub(pool.start_time)?;
+    pool.phases[..pool.phase_count as usize]
+        .iter()
+        .rev()
+        .find(|phase| phase.slot_offset <= elapsed)
+        .copied()
+}
+
+/// Resets the king-of-the-hill timer for a qualifying deposit and crowns the depositor.
+#[inline(always)]
+pub fn extend_pool_timer(pool: &mut Pool, depositor: Pubkey, amount: u64) {
+    let PoolMode::KingOfTheHill {
+        extension,
+        max_end_time,
+        ..
+    } = pool.mode
+    else {
+        return;
+    };
+    if amount < pool.min_deposit {
+        return;
+    }
+    pool.end_time = pool.end_time.saturating_add(extension).min(max_end_time);
+    pool.last_depositor = depositor;
+}
+
+pub fn assert_admin(admin_record: &UncheckedAccount, admin: &Signer) -> Result<()> {
+    // the admin key sits in the first 32 bytes of the record
+    if admin_record.data.borrow().get(..32) != Some(admin.key.as_ref()) {
+        return err!(ChickenError::Unauthorized);
+    }
+    Ok(())
+}
+
 #[inline(always)]
 pub fn assert_pool_active(pool: &Pool) -> std::result::Result<(), ChickenError> {
     match pool.state {
@@ -37,6 +143,7 @@ pub fn assert_pool_active(pool: &Pool) -> std::result::Result<(), ChickenError>
         PoolState::Ended => Err(ChickenError::PoolEnded),
         PoolState::Removed => Err(ChickenError::PoolRemoved),
         PoolState::Pending => Err(ChickenError::PoolPending),
+        PoolState::Refunding => Err(ChickenError::PoolRefunding),
     }
 }
 
@@ -45,16 +152,21 @@ pub fn assert_pool_withdrawable(pool: &Pool) -> std::result::Result<(), ChickenE
     match pool.state {
         PoolState::Started => Ok(()),
         PoolState::Ended => Ok(()),
+        PoolState::Refunding => Ok(()),
         PoolState::Removed => Err(ChickenError::PoolRemoved),
         PoolState::Pending => Err(ChickenError::PoolPending),
     }
 }
 
+/// Rounding policy: anything the pool pays out (payouts, collateral shares, prizes, bounties)
+/// goes through `bps` and rounds down. Fees and penalties the pool keeps go through `bps_up` and
+/// round up, so rounding can only leave dust in the vault, never a shortfall.
 #[inline(always)]
 pub fn bps(amount: u64, bps: u16) -> Result<u64> {
-    Ok((amount as u128)
-        .checked_mul(bps as u128)
-        .ok_or(ErrorCode::InvalidNumericConversion)?
-        .checked_div(10_000)
-        .ok_or(ErrorCode::InvalidNumericConversion)? as u64)
+    Ok(((amount as u128).safe_mul(bps as u128)? / 10_000) as u64)
+}
+
+#[inline(always)]
+pub fn bps_up(amount: u64, bps: u16) -> Result<u64> {
+    Ok((amount as u128).safe_mul(bps as u128)?.div_ceil(10_000) as u64)
 }
@@ -0,0 +1,55 @@
+use crate::state::{PlayerProfile, UserPosition};
+use anchor_lang::prelude::*;
+
+pub fn record_deposit(
+    profile: &mut PlayerProfile,
+    owner: Pubkey,
+    bump: u8,
+    amount: u64,
+    fee: u64,
+    new_position: bool,
+) {
+    profile.owner = owner;
+    profile.bump = bump;
+    if new_position {
+        profile.pools_joined = profile.pools_joined.saturating_add(1);
+    }
+    profile.total_deposited = profile.total_deposited.saturating_add(amount);
+    profile.total_fees_paid = profile.total_fees_paid.saturating_add(fee);
+}
+
+/// A player wins a pool when the payout before fees beats what they staked. A refund is
+/// neither a win nor a loss, and hands the deposit fee back.
+pub fn record_exit(
+    profile: &mut PlayerProfile,
+    bump: u8,
+    user_position: &UserPosition,
+    final_amount: u64,
+    fee: u64,
+    current_slot: u64,
+    refunded: bool,
+) -> bool {
+    profile.owner = user_position.owner;
+    profile.bump = bump;
+    // stats saturate so they can never block an exit
+    let staked = user_position
+        .deposit_amount
+        .saturating_add(user_position.collateral_amount);
+    let payout = final_amount.saturating_add(fee);
+    let won = !refunded && payout > staked;
+    if refunded {
+        profile.total_fees_paid = profile
+            .total_fees_paid
+            .saturating_sub(user_position.deposit_fee);
+    } else if won {
+        profile.wins = profile.wins.saturating_add(1);
+        profile.total_won = profile.total_won.saturating_add(payout - staked);
+    } else {
+        profile.losses = profile.losses.saturating_add(1);
+    }
+    profile.total_fees_paid = profile.total_fees_paid.saturating_add(fee);
+    profile.longest_hold = profile
+        .longest_hold
+        .max(current_slot.saturating_sub(user_position.deposit_time));
+    won
+}
@@ -0,0 +1,81 @@
+use super::{bps, update_pool_state};
+use crate::{
+    error::ChickenError,
+    events::PoolStateChanged,
+    math::SafeMath,
+    state::{Pool, PoolState},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::TransferChecked,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[derive(Accounts)]
+pub struct PokePool<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    pub keeper: Signer<'info>,
+    #[account(mut,
+        associated_token::mint = pool.collateral_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_collateral_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut,
+        token::mint = pool.collateral_mint,
+        token::authority = keeper,
+        token::token_program = token_program
+    )]
+    pub keeper_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn poke_pool(ctx: Context<PokePool>) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
+    let pool_info = ctx.accounts.pool.to_account_info();
+    let pool = &mut ctx.accounts.pool;
+    let previous = pool.state;
+    update_pool_state(pool, current_slot)?;
+    if pool.state == previous {
+        return err!(ChickenError::PoolStateUnchanged);
+    }
+    emit!(PoolStateChanged {
+        pool: pool.key(),
+        previous,
+        current: pool.state,
+        slot: current_slot,
+    });
+
+    // a refunding pool owes its deposit fees back to the players
+    if pool.state == PoolState::Refunding {
+        return Ok(());
+    }
+    let bounty = bps(pool.fee_amount, pool.keeper_bounty_bps)?;
+    if bounty == 0 {
+        return Ok(());
+    }
+    pool.fee_amount = pool.fee_amount.safe_sub(bounty)?;
+    anchor_spl::token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.pool_collateral_token_account.to_account_info(),
+                to: ctx.accounts.keeper_token_account.to_account_info(),
+                authority: pool_info,
+                mint: ctx.accounts.collateral_mint.to_account_info(),
+            },
+            &[&[
+                b"pool".as_ref(),
+                pool.pool_id.as_ref(),
+                pool.creator.as_ref(),
+                &[pool.bump],
+            ]],
+        ),
+        bounty,
+        ctx.accounts.collateral_mint.decimals,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use super::{
+    assert_pool_withdrawable, book_deposit, book_withdraw, check_deposit, in_sudden_death_window,
+    sudden_death_window_exit, update_pool_state,
+};
+use crate::{
+    error::ChickenError,
+    math::SafeMath,
+    state::{Pool, UserPosition},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+/// What a deposit or withdraw would book right now. For withdrawals `collateral` is everything
+/// paid out on top of the principal.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Quote {
+    pub principal: u64,
+    pub collateral: u64,
+    pub fee: u64,
+    pub final_amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct QuoteDeposit<'info> {
+    pub pool: Account<'info, Pool>,
+    #[account(
+      token::mint = pool.collateral_mint,
+      token::authority = pool,
+    )]
+    pub pool_collateral_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// The depositor's existing position, left out for a first deposit.
+    #[account(constraint = user_position.pool == pool.key())]
+    pub user_position: Option<Account<'info, UserPosition>>,
+}
+
+pub fn quote_deposit(ctx: Context<QuoteDeposit>, amount: u64) -> Result<Quote> {
+    let current_slot = Clock::get()?.slot;
+    let mut pool = ctx.accounts.pool.clone().into_inner();
+    let mut user_position = ctx
+        .accounts
+        .user_position
+        .clone()
+        .map(Account::into_inner)
+        .unwrap_or_default();
+    update_pool_state(&mut pool, current_slot)?;
+    if pool.share_mint.is_some() {
+        return err!(ChickenError::SharePool);
+    }
+    check_deposit(
+        &pool,
+        &user_position,
+        amount,
+        ctx.accounts.pool_collateral_token_account.amount,
+        current_slot,
+    )?;
+    let (fee, collateral) = book_deposit(&mut pool, &mut user_position, amount)?;
+    let principal = amount.safe_sub(fee)?.safe_sub(collateral)?;
+    Ok(Quote {
+        principal,
+        collateral,
+        fee,
+        final_amount: principal,
+    })
+}
+
+#[derive(Accounts)]
+pub struct QuoteWithdraw<'info> {
+    pub pool: Account<'info, Pool>,
+    #[account(constraint = user_position.pool == pool.key())]
+    pub user_position: Account<'info, UserPosition>,
+}
+
+pub fn quote_withdraw(ctx: Context<QuoteWithdraw>) -> Result<Quote> {
+    let current_slot = Clock::get()?.slot;
+    let mut pool = ctx.accounts.pool.clone().into_inner();
+    let mut user_position = ctx.accounts.user_position.clone().into_inner();
+    update_pool_state(&mut pool, current_slot)?;
+    assert_pool_withdrawable(&pool)?;
+    let principal = user_position.deposit_amount;
+    let (final_amount, fee) = if in_sudden_death_window(&pool, current_slot) {
+        let final_amount = sudden_death_window_exit(&mut pool, &mut user_position, current_slot)?;
+        (final_amount, principal.safe_sub(final_amount)?)
+    } else {
+        book_withdraw(&mut pool, &user_position, current_slot)?
+    };
+    Ok(Quote {
+        principal,
+        collateral: final_amount.safe_add(fee)?.saturating_sub(principal),
+        fee,
+        final_amount,
+    })
+}
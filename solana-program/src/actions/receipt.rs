@@ -0,0 +1,183 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    token_2022::{
+        self, spl_token_2022::instruction::AuthorityType, Burn, CloseAccount, MintTo, SetAuthority,
+        Token2022,
+    },
+    token_2022_extensions::{
+        spl_pod::optional_keys::OptionalNonZeroPubkey,
+        spl_token_metadata_interface::state::{Field, TokenMetadata},
+        token_metadata_initialize, token_metadata_update_field, TokenMetadataInitialize,
+        TokenMetadataUpdateField,
+    },
+    token_interface::{Mint, TokenAccount},
+};
+
+pub const RECEIPT_NAME: &str = "Chicken Position";
+pub const RECEIPT_SYMBOL: &str = "CHKN";
+pub const RECEIPT_POOL_FIELD: &str = "pool";
+
+/// Writes the receipt metadata, mints the single receipt token to `to` and drops the mint
+/// authority so the supply is fixed at one.
+#[allow(clippy::too_many_arguments)]
+pub fn mint_position_receipt<'info>(
+    pool_info: AccountInfo<'info>,
+    signer_seeds: &[&[u8]],
+    receipt_mint: &InterfaceAccount<'info, Mint>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    payer: AccountInfo<'info>,
+    token_program: &Program<'info, Token2022>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let mint_info = receipt_mint.to_account_info();
+    let metadata = TokenMetadata {
+        update_authority: OptionalNonZeroPubkey::try_from(Some(pool_info.key()))?,
+        mint: mint_info.key(),
+        name: RECEIPT_NAME.to_string(),
+        symbol: RECEIPT_SYMBOL.to_string(),
+        uri: String::new(),
+        additional_metadata: vec![(RECEIPT_POOL_FIELD.to_string(), pool_info.key().to_string())],
+    };
+    let required_lamports = Rent::get()?
+        .minimum_balance(mint_info.data_len() + metadata.tlv_size_of()?)
+        .saturating_sub(mint_info.lamports());
+    if required_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer,
+                    to: mint_info.clone(),
+                },
+            ),
+            required_lamports,
+        )?;
+    }
+
+    token_metadata_initialize(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TokenMetadataInitialize {
+                token_program_id: token_program.to_account_info(),
+                metadata: mint_info.clone(),
+                update_authority: pool_info.clone(),
+                mint_authority: pool_info.clone(),
+                mint: mint_info.clone(),
+            },
+            &[signer_seeds],
+        ),
+        metadata.name,
+        metadata.symbol,
+        metadata.uri,
+    )?;
+    token_metadata_update_field(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TokenMetadataUpdateField {
+                token_program_id: token_program.to_account_info(),
+                metadata: mint_info.clone(),
+                update_authority: pool_info.clone(),
+            },
+            &[signer_seeds],
+        ),
+        Field::Key(RECEIPT_POOL_FIELD.to_string()),
+        pool_info.key().to_string(),
+    )?;
+
+    token_2022::mint_to(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            MintTo {
+                mint: mint_info.clone(),
+                to: to.to_account_info(),
+                authority: pool_info.clone(),
+            },
+            &[signer_seeds],
+        ),
+        1,
+    )?;
+    token_2022::set_authority(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            SetAuthority {
+                current_authority: pool_info,
+                account_or_mint: mint_info,
+            },
+            &[signer_seeds],
+        ),
+        AuthorityType::MintTokens,
+        None,
+    )
+}
+
+/// Burns the receipt from `from`. The holder signs on exit; settle burns it with the pool's
+/// seeds as the mint's permanent delegate.
+pub fn burn_position_receipt<'info>(
+    receipt_mint: &InterfaceAccount<'info, Mint>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    authority: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    token_program: &Program<'info, Token2022>,
+) -> Result<()> {
+    token_2022::burn(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Burn {
+                mint: receipt_mint.to_account_info(),
+                from: from.to_account_info(),
+                authority,
+            },
+        )
+        .with_signer(signer_seeds),
+        1,
+    )
+}
+
+/// Closes the holder's emptied receipt token account and the receipt mint once the receipt is
+/// burnt. The pool is the mint's close authority, so the position's receipt PDA is free to be
+/// minted again if the owner rejoins.
+#[allow(clippy::too_many_arguments)]
+pub fn close_position_receipt<'info>(
+    pool_info: AccountInfo<'info>,
+    signer_seeds: &[&[u8]],
+    receipt_mint: &InterfaceAccount<'info, Mint>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    holder: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    token_program: &Program<'info, Token2022>,
+) -> Result<()> {
+    token_2022::close_account(CpiContext::new(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: from.to_account_info(),
+            destination: destination.clone(),
+            authority: holder,
+        },
+    ))?;
+    close_receipt_mint(
+        pool_info,
+        signer_seeds,
+        receipt_mint,
+        destination,
+        token_program,
+    )
+}
+
+/// Closes a receipt mint whose supply has been burnt, with the pool as close authority.
+pub fn close_receipt_mint<'info>(
+    pool_info: AccountInfo<'info>,
+    signer_seeds: &[&[u8]],
+    receipt_mint: &InterfaceAccount<'info, Mint>,
+    destination: AccountInfo<'info>,
+    token_program: &Program<'info, Token2022>,
+) -> Result<()> {
+    token_2022::close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: receipt_mint.to_account_info(),
+            destination,
+            authority: pool_info,
+        },
+        &[signer_seeds],
+    ))
+}
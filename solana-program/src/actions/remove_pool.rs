@@ -1,5 +1,6 @@
-use crate::state::*;
+use crate::{actions::pool_settled, error::ChickenError, state::*};
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
 
 #[derive(Accounts)]
 pub struct RemovePool<'info> {
@@ -11,9 +12,10 @@ pub struct RemovePool<'info> {
             creator.key().as_ref()
         ],
         bump = pool.bump,
-        constraint = pool.state == PoolState::Ended,
+        constraint = pool_settled(&pool),
         constraint = pool.users == 0,
         constraint = pool.collateral_amount == 0,
+        constraint = pool.total_principal == 0,
         constraint = pool.fee_amount == 0,
         realloc = 1,
         realloc::payer = payer,
@@ -25,10 +27,23 @@ pub struct RemovePool<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     system_program: Program<'info, System>,
+    #[account(
+        constraint = pool.share_mint == Some(share_mint.key()) @ ChickenError::SharesUnsupported
+    )]
+    pub share_mint: Option<InterfaceAccount<'info, Mint>>,
 }
 
 pub fn remove_pool(ctx: Context<RemovePool>) -> Result<()> {
     let pool = &mut ctx.accounts.pool;
+    // share holders are not counted in `users`, the outstanding supply stands in for them
+    if pool.share_mint.is_some() {
+        let Some(share_mint) = &ctx.accounts.share_mint else {
+            return err!(ChickenError::ShareMintRequired);
+        };
+        if share_mint.supply > 0 {
+            return err!(ChickenError::PoolHasUsers);
+        }
+    }
 
     let current_minimum_balance = Rent::get()?.minimum_balance(1);
     let previous_minimum_balance = Rent::get()?.minimum_balance(std::mem::size_of::<Pool>());
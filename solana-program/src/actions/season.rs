@@ -0,0 +1,178 @@
+use super::assert_admin;
+use crate::{
+    error::ChickenError,
+    state::{Season, SeasonEntry, SeasonLeader},
+    SEASON_WIN_POINTS,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::TransferChecked,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[derive(Accounts)]
+#[instruction(args: InitializeSeasonArgs)]
+pub struct InitializeSeason<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<Season>(),
+        seeds = [
+            b"season".as_ref(),
+            args.season_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub season: Box<Account<'info, Season>>,
+    #[account(
+        init,
+        payer = admin,
+        associated_token::mint = collateral_mint,
+        associated_token::authority = season,
+        associated_token::token_program = token_program
+    )]
+    pub season_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+      seeds = [
+        b"admin".as_ref(),
+      ],
+      bump
+    )]
+    /// CHECK: Admin account
+    pub admin_record: UncheckedAccount<'info>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct InitializeSeasonArgs {
+    pub season_id: u64,
+    pub start_slot: u64,
+    pub end_slot: u64,
+    pub fee_share_bps: u16,
+}
+
+pub fn initialize_season(ctx: Context<InitializeSeason>, args: InitializeSeasonArgs) -> Result<()> {
+    assert_admin(&ctx.accounts.admin_record, &ctx.accounts.admin)?;
+    if args.fee_share_bps > 10_000 {
+        return err!(ChickenError::InvalidSeasonFeeShare);
+    }
+    let season = &mut ctx.accounts.season;
+    season.season_id = args.season_id;
+    season.bump = ctx.bumps.season;
+    season.collateral_mint = ctx.accounts.collateral_mint.key();
+    season.start_slot = args.start_slot;
+    season.end_slot = args.end_slot;
+    season.fee_share_bps = args.fee_share_bps;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimSeasonPrize<'info> {
+    #[account(mut)]
+    pub season: Box<Account<'info, Season>>,
+    #[account(mut,
+        associated_token::mint = season.collateral_mint,
+        associated_token::authority = season,
+        associated_token::token_program = token_program
+    )]
+    pub season_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub player: Signer<'info>,
+    #[account(mut,
+        token::mint = season.collateral_mint,
+        token::authority = player,
+        token::token_program = token_program
+    )]
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn claim_season_prize(ctx: Context<ClaimSeasonPrize>) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
+    let season_info = ctx.accounts.season.to_account_info();
+    let season = &mut ctx.accounts.season;
+    if current_slot <= season.end_slot {
+        return err!(ChickenError::SeasonNotOver);
+    }
+    let total_points: u128 = season
+        .leaders
+        .iter()
+        .map(|leader| leader.points as u128)
+        .sum();
+    let prize_pool = season.prize_pool;
+    let player = ctx.accounts.player.key();
+    let Some(leader) = season
+        .leaders
+        .iter_mut()
+        .find(|leader| leader.player == player && leader.points > 0 && !leader.claimed)
+    else {
+        return err!(ChickenError::NoSeasonPrize);
+    };
+    leader.claimed = true;
+    let prize = (prize_pool as u128 * leader.points as u128 / total_points) as u64;
+
+    anchor_spl::token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.season_token_account.to_account_info(),
+                to: ctx.accounts.player_token_account.to_account_info(),
+                authority: season_info,
+                mint: ctx.accounts.collateral_mint.to_account_info(),
+            },
+            &[&[
+                b"season".as_ref(),
+                season.season_id.to_le_bytes().as_ref(),
+                &[season.bump],
+            ]],
+        ),
+        prize,
+        ctx.accounts.collateral_mint.decimals,
+    )?;
+    Ok(())
+}
+
+/// Points for one exit: a slot per slot held plus a bonus when the exit was a win.
+pub fn season_points(hold_slots: u64, won: bool) -> u64 {
+    if won {
+        hold_slots.saturating_add(SEASON_WIN_POINTS)
+    } else {
+        hold_slots
+    }
+}
+
+pub fn accrue_season_points(
+    season: &mut Account<Season>,
+    season_entry: &mut SeasonEntry,
+    player: Pubkey,
+    points: u64,
+) {
+    season_entry.season = season.key();
+    season_entry.player = player;
+    season_entry.points = season_entry.points.saturating_add(points);
+    update_leaderboard(&mut season.leaders, player, season_entry.points);
+}
+
+/// Keeps `leaders` as the players with the most points, replacing the lowest entry when full.
+pub fn update_leaderboard(leaders: &mut [SeasonLeader], player: Pubkey, points: u64) {
+    if let Some(leader) = leaders.iter_mut().find(|leader| leader.player == player) {
+        leader.points = points;
+        return;
+    }
+    let Some(lowest) = leaders.iter_mut().min_by_key(|leader| leader.points) else {
+        return;
+    };
+    if lowest.player == Pubkey::default() || points > lowest.points {
+        *lowest = SeasonLeader {
+            player,
+            points,
+            claimed: false,
+        };
+    }
+}
@@ -0,0 +1,29 @@
+use crate::state::{Pool, PositionDelegate, UserPosition};
+use anchor_lang::prelude::*;
+
+/// Lets `delegate.authority` withdraw the position to the owner's token account until
+/// `delegate.expiry_slot`. Passing `None` revokes the delegate.
+#[derive(Accounts)]
+pub struct SetPositionDelegate<'info> {
+    pub pool: Account<'info, Pool>,
+    pub owner: Signer<'info>,
+    #[account(mut,
+        seeds = [
+            b"user_position".as_ref(),
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+        constraint = user_position.owner == owner.key(),
+        constraint = user_position.pool == pool.key(),
+    )]
+    pub user_position: Account<'info, UserPosition>,
+}
+
+pub fn set_position_delegate(
+    ctx: Context<SetPositionDelegate>,
+    delegate: Option<PositionDelegate>,
+) -> Result<()> {
+    ctx.accounts.user_position.delegate = delegate;
+    Ok(())
+}
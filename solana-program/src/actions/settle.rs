@@ -0,0 +1,180 @@
+use super::{
+    bps, burn_position_receipt, close_receipt_mint, pay_slashed_bond, process_withdraw,
+    record_exit, update_pool_state,
+};
+use crate::{
+    error::ChickenError,
+    math::SafeMath,
+    state::{PlayerProfile, Pool, PoolState, UserPosition},
+    SETTLE_BOUNTY_BPS,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::{Token2022, TransferChecked},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+/// Settles abandoned positions of an ended pool. `remaining_accounts` holds four accounts per
+/// position: the `UserPosition`, the owner's collateral token account, the account that
+/// paid the position rent and the owner's `PlayerProfile`. Receipt-backed positions pay the
+/// receipt holder instead: their collateral token account takes the owner's slot, and the
+/// holder's receipt token account and the receipt mint follow as a fifth and sixth account so
+/// the receipt can be burnt and its mint closed.
+#[derive(Accounts)]
+pub struct Settle<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    pub cranker: Signer<'info>,
+    #[account(mut,
+        associated_token::mint = pool.collateral_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_collateral_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut,
+        token::mint = pool.collateral_mint,
+        token::authority = cranker,
+        token::token_program = token_program
+    )]
+    pub cranker_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub receipt_token_program: Option<Program<'info, Token2022>>,
+}
+
+pub fn settle<'info>(ctx: Context<'_, '_, 'info, 'info, Settle<'info>>) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
+    let pool_info = ctx.accounts.pool.to_account_info();
+    let pool_key = ctx.accounts.pool.key();
+    let pool = &mut ctx.accounts.pool;
+    update_pool_state(pool, current_slot)?;
+    if pool.state != PoolState::Ended && pool.state != PoolState::Refunding {
+        return err!(ChickenError::PoolNotEnded);
+    }
+    if ctx.remaining_accounts.is_empty() {
+        return err!(ChickenError::InvalidSettleAccounts);
+    }
+
+    let (pool_id, creator, bump) = (pool.pool_id, pool.creator, pool.bump);
+    let signer_seeds: &[&[u8]] = &[
+        b"pool".as_ref(),
+        pool_id.as_ref(),
+        creator.as_ref(),
+        &[bump],
+    ];
+    let mut fees_collected = 0u64;
+    let mut remaining = ctx.remaining_accounts;
+    while !remaining.is_empty() {
+        let user_position = Account::<UserPosition>::try_from(&remaining[0])?;
+        let width = if user_position.receipt_mint.is_some() {
+            6
+        } else {
+            4
+        };
+        if remaining.len() < width {
+            return err!(ChickenError::InvalidSettleAccounts);
+        }
+        let (accounts, rest) = remaining.split_at(width);
+        remaining = rest;
+        let owner_token_account = InterfaceAccount::<TokenAccount>::try_from(&accounts[1])?;
+        let rent_receiver = &accounts[2];
+        let mut player_profile = Account::<PlayerProfile>::try_from(&accounts[3])?;
+        let holder = match user_position.receipt_mint {
+            Some(receipt_mint) => {
+                let receipt_token_account =
+                    InterfaceAccount::<TokenAccount>::try_from(&accounts[4])?;
+                if receipt_token_account.mint != receipt_mint
+                    || receipt_token_account.amount != 1
+                    || accounts[5].key() != receipt_mint
+                {
+                    return err!(ChickenError::InvalidPositionReceipt);
+                }
+                receipt_token_account.owner
+            }
+            None => user_position.owner,
+        };
+        if user_position.pool != pool_key
+            || owner_token_account.owner != holder
+            || owner_token_account.mint != pool.collateral_mint
+            || rent_receiver.key() != user_position.payer
+            || player_profile.owner != user_position.owner
+        {
+            return err!(ChickenError::InvalidSettleAccounts);
+        }
+
+        let (final_amount, fee) = process_withdraw(pool, &user_position, current_slot)?;
+        pay_slashed_bond(pool, &user_position)?;
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.pool_collateral_token_account.to_account_info(),
+                    to: accounts[1].clone(),
+                    authority: pool_info.clone(),
+                    mint: ctx.accounts.collateral_mint.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            final_amount,
+            ctx.accounts.collateral_mint.decimals,
+        )?;
+        let bump = player_profile.bump;
+        record_exit(
+            &mut player_profile,
+            bump,
+            &user_position,
+            final_amount,
+            fee,
+            current_slot,
+            pool.state == PoolState::Refunding,
+        );
+        player_profile.exit(&crate::ID)?;
+        if user_position.receipt_mint.is_some() {
+            // the position is paid out, so the receipt can't be left around to be sold again
+            let Some(receipt_token_program) = &ctx.accounts.receipt_token_program else {
+                return err!(ChickenError::PositionReceiptRequired);
+            };
+            let receipt_mint = InterfaceAccount::<Mint>::try_from(&accounts[5])?;
+            let receipt_token_account = InterfaceAccount::<TokenAccount>::try_from(&accounts[4])?;
+            burn_position_receipt(
+                &receipt_mint,
+                &receipt_token_account,
+                pool_info.clone(),
+                &[signer_seeds],
+                receipt_token_program,
+            )?;
+            close_receipt_mint(
+                pool_info.clone(),
+                signer_seeds,
+                &receipt_mint,
+                rent_receiver.clone(),
+                receipt_token_program,
+            )?;
+        }
+        fees_collected = fees_collected.safe_add(fee)?;
+        user_position.close(rent_receiver.clone())?;
+    }
+
+    if let Some(cranker_token_account) = &ctx.accounts.cranker_token_account {
+        let bounty = bps(fees_collected, SETTLE_BOUNTY_BPS)?;
+        if bounty > 0 {
+            pool.fee_amount = pool.fee_amount.safe_sub(bounty)?;
+            anchor_spl::token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.pool_collateral_token_account.to_account_info(),
+                        to: cranker_token_account.to_account_info(),
+                        authority: pool_info,
+                        mint: ctx.accounts.collateral_mint.to_account_info(),
+                    },
+                    &[signer_seeds],
+                ),
+                bounty,
+                ctx.accounts.collateral_mint.decimals,
+            )?;
+        }
+    }
+
+    Ok(())
+}
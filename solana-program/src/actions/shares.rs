@@ -0,0 +1,210 @@
+use super::{assert_pool_withdrawable, book_deposit, bps_up, check_deposit, update_pool_state};
+use crate::{
+    error::ChickenError,
+    math::SafeMath,
+    state::{Pool, UserPosition},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::{Burn, MintTo, TransferChecked},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[derive(Accounts)]
+pub struct DepositShares<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    pub user: Signer<'info>,
+    #[account(mut,
+      associated_token::mint = pool.collateral_mint,
+      associated_token::authority = pool,
+      associated_token::token_program = token_program
+    )]
+    pub pool_collateral_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut,
+      token::mint = pool.collateral_mint,
+      token::authority = user,
+      token::token_program = token_program
+    )]
+    pub user_collateral_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut,
+      constraint = pool.share_mint == Some(share_mint.key()) @ ChickenError::SharesUnsupported
+    )]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut,
+      token::mint = share_mint,
+      token::authority = user,
+      token::token_program = token_program
+    )]
+    pub user_share_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// `max_fee_bps` and `max_collateral_bps` bound the rates the caller quoted against.
+pub fn deposit_shares(
+    ctx: Context<DepositShares>,
+    amount: u64,
+    max_fee_bps: u16,
+    max_collateral_bps: u16,
+) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
+    let pool_info = ctx.accounts.pool.to_account_info();
+    let pool = &mut ctx.accounts.pool;
+    let token_account_amount = ctx.accounts.pool_collateral_token_account.amount;
+    update_pool_state(pool, current_slot)?;
+    // shares open no position, so a fresh one stands in for the checks and the booking. Shares
+    // are transferable, so the per-user limit can only bound a single deposit, and a non-zero
+    // `deposit_time` keeps share holders out of the user count.
+    let mut user_position = UserPosition {
+        deposit_time: current_slot,
+        ..Default::default()
+    };
+    check_deposit(
+        pool,
+        &user_position,
+        amount,
+        token_account_amount,
+        current_slot,
+    )?;
+    if pool.deposit_fee_bps > max_fee_bps || pool.collateral_bps > max_collateral_bps {
+        return err!(ChickenError::FeeLimitExceeded);
+    }
+    let total_principal = pool.total_principal;
+    book_deposit(pool, &mut user_position, amount)?;
+    let principal = user_position.deposit_amount;
+    let supply = ctx.accounts.share_mint.supply;
+    let shares = if supply == 0 || total_principal == 0 {
+        principal
+    } else {
+        (principal as u128 * supply as u128 / total_principal as u128) as u64
+    };
+    if shares == 0 {
+        return err!(ChickenError::InvalidShareAmount);
+    }
+
+    anchor_spl::token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.user_collateral_token_account.to_account_info(),
+                to: ctx.accounts.pool_collateral_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+                mint: ctx.accounts.collateral_mint.to_account_info(),
+            },
+        ),
+        amount,
+        ctx.accounts.collateral_mint.decimals,
+    )?;
+
+    anchor_spl::token_interface::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                to: ctx.accounts.user_share_token_account.to_account_info(),
+                authority: pool_info,
+            },
+            &[&[
+                b"pool".as_ref(),
+                pool.pool_id.as_ref(),
+                pool.creator.as_ref(),
+                &[pool.bump],
+            ]],
+        ),
+        shares,
+    )?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawShares<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    pub user: Signer<'info>,
+    #[account(mut,
+        associated_token::mint = pool.collateral_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_collateral_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut,
+        token::mint = pool.collateral_mint,
+        token::authority = user,
+        token::token_program = token_program
+    )]
+    pub user_collateral_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut,
+      constraint = pool.share_mint == Some(share_mint.key()) @ ChickenError::SharesUnsupported
+    )]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut,
+      token::mint = share_mint,
+      token::authority = user,
+      token::token_program = token_program
+    )]
+    pub user_share_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn withdraw_shares(ctx: Context<WithdrawShares>, shares: u64) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
+    let pool_info = ctx.accounts.pool.to_account_info();
+    let pool = &mut ctx.accounts.pool;
+    update_pool_state(pool, current_slot)?;
+    assert_pool_withdrawable(pool)?;
+
+    let supply = ctx.accounts.share_mint.supply;
+    if shares == 0 || shares > supply {
+        return err!(ChickenError::InvalidShareAmount);
+    }
+    let total_principal = pool.total_principal;
+    let principal = (shares as u128 * total_principal as u128 / supply as u128) as u64;
+    // burning the whole supply makes the caller the last one out
+    let collateral = if shares == supply {
+        pool.collateral_amount
+    } else {
+        0
+    };
+    let payout = principal.safe_add(collateral)?;
+    let fee = bps_up(payout, pool.withdraw_fee_bps)?;
+    let final_amount = payout.safe_sub(fee)?;
+    pool.fee_amount = pool.fee_amount.safe_add(fee)?;
+    pool.collateral_amount = pool.collateral_amount.safe_sub(collateral)?;
+    pool.total_principal = pool.total_principal.safe_sub(principal)?;
+    pool.withdrawn = pool.withdrawn.safe_add(final_amount)?;
+
+    anchor_spl::token_interface::burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                from: ctx.accounts.user_share_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        shares,
+    )?;
+    anchor_spl::token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.pool_collateral_token_account.to_account_info(),
+                to: ctx.accounts.user_collateral_token_account.to_account_info(),
+                authority: pool_info,
+                mint: ctx.accounts.collateral_mint.to_account_info(),
+            },
+            &[&[
+                b"pool".as_ref(),
+                pool.pool_id.as_ref(),
+                pool.creator.as_ref(),
+                &[pool.bump],
+            ]],
+        ),
+        final_amount,
+        ctx.accounts.collateral_mint.decimals,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,238 @@
+use super::bps_up;
+use crate::{
+    error::ChickenError,
+    math::SafeMath,
+    state::{Pool, PoolState, SuddenDeath, UserPosition},
+    SUDDEN_DEATH_BUCKETS, SUDDEN_DEATH_REVEAL_TIMEOUT,
+};
+use anchor_lang::{
+    prelude::*,
+    solana_program::{hash::hashv, slot_hashes::MAX_ENTRIES, sysvar::slot_hashes},
+};
+
+#[derive(Accounts)]
+pub struct RevealEnd<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    #[account(mut)]
+    pub revealer: Signer<'info>,
+    /// CHECK: address is checked against the SlotHashes sysvar
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+/// Fixes the hidden end of a sudden-death pool and sizes the winners' share of the pot from the
+/// window exits tallied so far.
+pub fn reveal_end(ctx: Context<RevealEnd>, secret: [u8; 32], salt: [u8; 32]) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
+    let pool = &mut ctx.accounts.pool;
+    let Some(mut sudden_death) = pool.sudden_death else {
+        return err!(ChickenError::InvalidPoolMode);
+    };
+    if sudden_death.revealed_end.is_some() {
+        return err!(ChickenError::EndAlreadyRevealed);
+    }
+    if current_slot <= pool.end_time {
+        return err!(ChickenError::RevealTooEarly);
+    }
+    let honest = ctx.accounts.revealer.key() == pool.creator;
+    let seed = if honest {
+        if hashv(&[&secret, &salt]).to_bytes() != sudden_death.commitment {
+            return err!(ChickenError::InvalidReveal);
+        }
+        secret
+    } else {
+        // the creator sat on the secret, so anyone may settle the end and the bond is slashed to
+        // the winners
+        if current_slot <= pool.end_time.safe_add(SUDDEN_DEATH_REVEAL_TIMEOUT)? {
+            return err!(ChickenError::RevealTooEarly);
+        }
+        sudden_death.commitment
+    };
+    // the block at the end slot did not exist when the commitment was made, and picking the
+    // reveal slot cannot change it. Past the sysvar window only the seed is left to go on.
+    let block_hash = block_hash_since(&ctx.accounts.slot_hashes.data.borrow(), pool.end_time)?;
+    let mixed = match block_hash {
+        Some(block_hash) => hashv(&[&seed, &block_hash]),
+        None => hashv(&[&seed]),
+    }
+    .to_bytes();
+    let bucket_len = exit_bucket_len(pool.end_time, &sudden_death)?;
+    let buckets = pool
+        .end_time
+        .safe_sub(sudden_death.earliest_end)?
+        .safe_add(1)?
+        .div_ceil(bucket_len);
+    let end_bucket = u64::from_le_bytes(mixed[..8].try_into().unwrap()) % buckets;
+    let revealed_end = sudden_death
+        .earliest_end
+        .safe_add(end_bucket.safe_mul(bucket_len)?)?;
+    // everyone who left in a bucket before the end lost their collateral to the pot
+    let losing_collateral = sudden_death.exit_buckets[..end_bucket as usize]
+        .iter()
+        .try_fold(0u64, |total, collateral| total.safe_add(*collateral))?;
+
+    sudden_death.revealed_end = Some(revealed_end);
+    sudden_death.pot = pool.collateral_amount;
+    sudden_death.winning_collateral = sudden_death
+        .at_risk_collateral
+        .safe_sub(losing_collateral)?;
+    if honest && sudden_death.bond > 0 {
+        pool.sub_lamports(sudden_death.bond)?;
+        ctx.accounts.revealer.add_lamports(sudden_death.bond)?;
+        sudden_death.bond = 0;
+    }
+    pool.sudden_death = Some(sudden_death);
+    Ok(())
+}
+
+/// Moves a winning position's share of a slashed bond onto the position account, so it is paid
+/// out with the position's rent when the position closes. Winners split the bond by collateral,
+/// the same way they split the pot. With no winners the bond stays with the pool.
+pub fn pay_slashed_bond<'info>(
+    pool: &Account<'info, Pool>,
+    user_position: &Account<'info, UserPosition>,
+) -> Result<()> {
+    let Some(sudden_death) = pool.sudden_death else {
+        return Ok(());
+    };
+    let Some(revealed_end) = sudden_death.revealed_end else {
+        return Ok(());
+    };
+    if sudden_death.bond == 0
+        || pool.state == PoolState::Refunding
+        || !won_sudden_death(user_position, revealed_end)
+    {
+        return Ok(());
+    }
+    let share = (sudden_death.bond as u128 * user_position.collateral_amount as u128)
+        .checked_div(sudden_death.winning_collateral as u128)
+        .unwrap_or_default() as u64;
+    if share > 0 {
+        pool.sub_lamports(share)?;
+        user_position.add_lamports(share)?;
+    }
+    Ok(())
+}
+
+/// Positions still in at the revealed end, or that left on or after it, won.
+fn won_sudden_death(user_position: &UserPosition, revealed_end: u64) -> bool {
+    user_position.exit_slot == 0 || user_position.exit_slot >= revealed_end
+}
+
+/// Window exits are tallied in buckets of this many slots, and the revealed end always falls on
+/// a bucket boundary, so a bucket's exits either all won or all lost.
+pub fn exit_bucket_len(end_time: u64, sudden_death: &SuddenDeath) -> Result<u64> {
+    Ok(end_time
+        .safe_sub(sudden_death.earliest_end)?
+        .safe_add(1)?
+        .div_ceil(SUDDEN_DEATH_BUCKETS as u64))
+}
+
+/// Reads the hash of the first block at or after `slot` out of the raw SlotHashes data, whose
+/// `(slot, hash)` entries run from the newest slot back. `None` once the sysvar no longer
+/// reaches back to `slot`.
+fn block_hash_since(slot_hashes: &[u8], slot: u64) -> Result<Option<[u8; 32]>> {
+    let len = slot_hashes.get(..8).map_or(0, |len| {
+        u64::from_le_bytes(len.try_into().unwrap()) as usize
+    });
+    let entries = slot_hashes.get(8..).unwrap_or_default().chunks_exact(40);
+    let mut hash = None;
+    // a sysvar that is not full yet still holds every block since genesis
+    let mut covered = len < MAX_ENTRIES;
+    for entry in entries.take(len) {
+        let entry_slot = u64::from_le_bytes(entry[..8].try_into().unwrap());
+        if entry_slot < slot {
+            covered = true;
+            break;
+        }
+        hash = Some(entry[8..].try_into().unwrap());
+        if entry_slot == slot {
+            covered = true;
+            break;
+        }
+    }
+    match hash {
+        Some(hash) if covered => Ok(Some(hash)),
+        // no block has landed since `slot` yet
+        None if covered => err!(ChickenError::RevealTooEarly),
+        _ => Ok(None),
+    }
+}
+
+/// Whether a withdrawal now has to wait for the reveal to learn if it won.
+pub fn in_sudden_death_window(pool: &Pool, current_slot: u64) -> bool {
+    pool.state != PoolState::Refunding
+        && pool.sudden_death.is_some_and(|sudden_death| {
+            sudden_death.revealed_end.is_none() && current_slot >= sudden_death.earliest_end
+        })
+}
+
+/// Pays out the principal of a position leaving inside the window and keeps the position open
+/// so its collateral can be claimed once the end is revealed.
+pub fn sudden_death_window_exit(
+    pool: &mut Pool,
+    user_position: &mut UserPosition,
+    current_slot: u64,
+) -> Result<u64> {
+    let end_time = pool.end_time;
+    let Some(sudden_death) = pool.sudden_death.as_mut() else {
+        return err!(ChickenError::InvalidPoolMode);
+    };
+    if user_position.exit_slot != 0 {
+        return err!(ChickenError::EndNotRevealed);
+    }
+    let bucket = current_slot.safe_sub(sudden_death.earliest_end)?
+        / exit_bucket_len(end_time, sudden_death)?;
+    let bucket = (bucket as usize).min(SUDDEN_DEATH_BUCKETS - 1);
+    sudden_death.exit_buckets[bucket] =
+        sudden_death.exit_buckets[bucket].safe_add(user_position.collateral_amount)?;
+    sudden_death.window_exits = sudden_death.window_exits.safe_add(1)?;
+    let fee = bps_up(user_position.deposit_amount, pool.withdraw_fee_bps)?;
+    let final_amount = user_position.deposit_amount.safe_sub(fee)?;
+    pool.fee_amount = pool.fee_amount.safe_add(fee)?;
+    pool.withdrawn = pool.withdrawn.safe_add(final_amount)?;
+    pool.total_principal = pool
+        .total_principal
+        .safe_sub(user_position.deposit_amount)?;
+    user_position.exit_slot = current_slot;
+    user_position.deposit_amount = 0;
+    Ok(final_amount)
+}
+
+/// Returns what a sudden-death position is owed on close and how much of it is collateral.
+pub fn sudden_death_return(
+    pool: &mut Pool,
+    user_position: &UserPosition,
+    current_slot: u64,
+) -> Result<(u64, u64)> {
+    let Some(mut sudden_death) = pool.sudden_death else {
+        return err!(ChickenError::InvalidPoolMode);
+    };
+    if current_slot < sudden_death.earliest_end {
+        // the end cannot have fallen yet, so this is a plain loss of collateral
+        sudden_death.at_risk_collateral = sudden_death
+            .at_risk_collateral
+            .safe_sub(user_position.collateral_amount)?;
+        pool.sudden_death = Some(sudden_death);
+        return Ok((user_position.deposit_amount, 0));
+    }
+    let Some(revealed_end) = sudden_death.revealed_end else {
+        return err!(ChickenError::EndNotRevealed);
+    };
+    let won = won_sudden_death(user_position, revealed_end);
+    let share = if !won {
+        0
+    } else if pool.users == 1 {
+        pool.collateral_amount
+    } else {
+        (sudden_death.pot as u128 * user_position.collateral_amount as u128)
+            .checked_div(sudden_death.winning_collateral as u128)
+            .unwrap_or_default() as u64
+    };
+    if !won && pool.users == 1 {
+        pool.fee_amount = pool.fee_amount.safe_add(pool.collateral_amount)?;
+        pool.collateral_amount = 0;
+    }
+    Ok((user_position.deposit_amount.safe_add(share)?, share))
+}
@@ -0,0 +1,87 @@
+use super::{assert_pool_active, update_pool_state};
+use crate::{
+    error::ChickenError,
+    math::SafeMath,
+    state::{Pool, PoolMode, PoolState, UserPosition},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CheckIn<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    pub owner: Signer<'info>,
+    #[account(mut,
+        seeds = [
+            b"user_position".as_ref(),
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+        constraint = user_position.owner == owner.key(),
+        constraint = user_position.pool == pool.key(),
+    )]
+    pub user_position: Account<'info, UserPosition>,
+}
+
+pub fn check_in(ctx: Context<CheckIn>) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
+    let pool = &mut ctx.accounts.pool;
+    update_pool_state(pool, current_slot)?;
+    assert_pool_active(pool)?;
+    let user_position = &mut ctx.accounts.user_position;
+    if !matches!(pool.mode, PoolMode::Survival { .. }) {
+        return err!(ChickenError::InvalidPoolMode);
+    }
+    if user_position.eliminated {
+        return err!(ChickenError::PositionEliminated);
+    }
+    if missed_check_in(pool, user_position, current_slot) {
+        return err!(ChickenError::MissedCheckIn);
+    }
+    user_position.last_check_in = current_slot;
+    Ok(())
+}
+
+/// Permissionless: anyone holding proof of a missed heartbeat can knock the position out.
+#[derive(Accounts)]
+pub struct Eliminate<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    pub cranker: Signer<'info>,
+    #[account(mut,
+        constraint = user_position.pool == pool.key(),
+    )]
+    pub user_position: Account<'info, UserPosition>,
+}
+
+pub fn eliminate(ctx: Context<Eliminate>) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
+    let pool = &mut ctx.accounts.pool;
+    update_pool_state(pool, current_slot)?;
+    if pool.state == PoolState::Removed {
+        return err!(ChickenError::PoolRemoved);
+    }
+    let user_position = &mut ctx.accounts.user_position;
+    if user_position.eliminated {
+        return err!(ChickenError::PositionEliminated);
+    }
+    if !missed_check_in(pool, user_position, current_slot) {
+        return err!(ChickenError::CheckInNotMissed);
+    }
+    user_position.eliminated = true;
+    pool.open_collateral = pool
+        .open_collateral
+        .safe_sub(user_position.collateral_amount)?;
+    Ok(())
+}
+
+/// A heartbeat counts as missed once the interval after `last_check_in` has run out, as long as
+/// that happened before the pool ended.
+pub fn missed_check_in(pool: &Pool, user_position: &UserPosition, current_slot: u64) -> bool {
+    let PoolMode::Survival { interval } = pool.mode else {
+        return false;
+    };
+    let deadline = user_position.last_check_in.saturating_add(interval);
+    deadline < current_slot.min(pool.end_time)
+}
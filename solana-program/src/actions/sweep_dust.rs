@@ -0,0 +1,106 @@
+use crate::{
+    actions::{assert_admin, pool_settled, update_pool_state},
+    error::ChickenError,
+    state::{Pool, Treasury},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::TransferChecked,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[derive(Accounts)]
+pub struct SweepDust<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    #[account(mut,
+        associated_token::mint = pool.collateral_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_collateral_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [
+            b"treasury".as_ref(),
+            pool.collateral_mint.as_ref(),
+        ],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    #[account(mut,
+        associated_token::mint = pool.collateral_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [
+            b"admin".as_ref(),
+        ],
+        bump
+    )]
+    /// CHECK: Admin account
+    pub admin_record: UncheckedAccount<'info>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(
+        constraint = pool.share_mint == Some(share_mint.key()) @ ChickenError::SharesUnsupported
+    )]
+    pub share_mint: Option<InterfaceAccount<'info, Mint>>,
+}
+
+/// Moves everything in the vault that is not owed as fees to the treasury once the pool is over
+/// and every position has left: rounding dust, unclaimed collateral and direct transfers to the
+/// vault.
+pub fn sweep_dust(ctx: Context<SweepDust>) -> Result<()> {
+    assert_admin(&ctx.accounts.admin_record, &ctx.accounts.admin)?;
+    let pool_info = ctx.accounts.pool.to_account_info();
+    let pool = &mut ctx.accounts.pool;
+    update_pool_state(pool, Clock::get()?.slot)?;
+    if pool.users > 0 || pool.total_principal > 0 {
+        return err!(ChickenError::PoolHasUsers);
+    }
+    // share holders are not counted in `users`, the outstanding supply stands in for them
+    if pool.share_mint.is_some() {
+        let Some(share_mint) = &ctx.accounts.share_mint else {
+            return err!(ChickenError::ShareMintRequired);
+        };
+        if share_mint.supply > 0 {
+            return err!(ChickenError::PoolHasUsers);
+        }
+    }
+    if !pool_settled(pool) {
+        return err!(ChickenError::PoolNotEnded);
+    }
+    let dust = ctx
+        .accounts
+        .pool_collateral_token_account
+        .amount
+        .saturating_sub(pool.fee_amount);
+    if dust == 0 {
+        return err!(ChickenError::NothingToSweep);
+    }
+    pool.collateral_amount = 0;
+    pool.open_collateral = 0;
+    anchor_spl::token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.pool_collateral_token_account.to_account_info(),
+                to: ctx.accounts.treasury_token_account.to_account_info(),
+                authority: pool_info,
+                mint: ctx.accounts.collateral_mint.to_account_info(),
+            },
+            &[&[
+                b"pool".as_ref(),
+                pool.pool_id.as_ref(),
+                pool.creator.as_ref(),
+                &[pool.bump],
+            ]],
+        ),
+        dust,
+        ctx.accounts.collateral_mint.decimals,
+    )?;
+    Ok(())
+}
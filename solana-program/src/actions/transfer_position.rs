@@ -0,0 +1,125 @@
+use super::{assert_pool_withdrawable, update_pool_state};
+use crate::{
+    error::ChickenError,
+    math::SafeMath,
+    state::{ExitOrder, PlayerProfile, Pool, UserPosition},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(new_owner: Pubkey)]
+pub struct TransferPosition<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut,
+        seeds = [
+            b"user_position".as_ref(),
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+        constraint = user_position.owner == owner.key(),
+        constraint = user_position.pool == pool.key(),
+        close = rent_receiver
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + std::mem::size_of::<UserPosition>(),
+        seeds = [
+            b"user_position".as_ref(),
+            pool.key().as_ref(),
+            new_owner.as_ref(),
+        ],
+        bump
+    )]
+    pub new_user_position: Account<'info, UserPosition>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: whoever funded the source position gets its rent back
+    #[account(mut, address = user_position.payer)]
+    pub rent_receiver: UncheckedAccount<'info>,
+    /// An exit order on the source position would otherwise outlive it.
+    #[account(mut,
+        seeds = [
+            b"exit_order".as_ref(),
+            user_position.key().as_ref(),
+        ],
+        bump,
+        close = owner
+    )]
+    pub exit_order: Option<Account<'info, ExitOrder>>,
+    /// The new owner needs a profile for its exit to be recorded, settle included.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + std::mem::size_of::<PlayerProfile>(),
+        seeds = [
+            b"player_profile".as_ref(),
+            new_owner.as_ref(),
+        ],
+        bump
+    )]
+    pub new_owner_profile: Account<'info, PlayerProfile>,
+}
+
+pub fn transfer_position(ctx: Context<TransferPosition>, new_owner: Pubkey) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
+    let pool = &mut ctx.accounts.pool;
+    update_pool_state(pool, current_slot)?;
+    assert_pool_withdrawable(pool)?;
+    let user_position = &ctx.accounts.user_position;
+    let new_user_position = &mut ctx.accounts.new_user_position;
+    if !pool.transferable
+        || new_owner == ctx.accounts.owner.key()
+        || user_position.receipt_mint.is_some()
+        || user_position.exit_slot != 0
+        || user_position.eliminated
+        || new_user_position.exit_slot != 0
+        || new_user_position.eliminated
+        || new_user_position.receipt_mint.is_some()
+    {
+        return err!(ChickenError::PositionNotTransferable);
+    }
+
+    let new_owner_profile = &mut ctx.accounts.new_owner_profile;
+    new_owner_profile.owner = new_owner;
+    new_owner_profile.bump = ctx.bumps.new_owner_profile;
+
+    if new_user_position.deposit_time == 0 {
+        new_user_position.owner = new_owner;
+        new_user_position.pool = pool.key();
+        new_user_position.payer = ctx.accounts.payer.key();
+        new_user_position.deposit_time = user_position.deposit_time;
+        new_user_position.last_check_in = user_position.last_check_in;
+    } else {
+        new_user_position.last_check_in = new_user_position
+            .last_check_in
+            .min(user_position.last_check_in);
+        let total = user_position.deposit_amount as u128 + new_user_position.deposit_amount as u128;
+        let weighted_time = user_position.deposit_time as u128
+            * user_position.deposit_amount as u128
+            + new_user_position.deposit_time as u128 * new_user_position.deposit_amount as u128;
+        if let Some(deposit_time) = weighted_time.checked_div(total) {
+            new_user_position.deposit_time = deposit_time as u64;
+        }
+        pool.users = pool.users.safe_sub(1)?;
+    }
+    if pool.last_depositor == user_position.owner {
+        pool.last_depositor = new_owner;
+    }
+    new_user_position.deposit_amount = new_user_position
+        .deposit_amount
+        .safe_add(user_position.deposit_amount)?;
+    new_user_position.collateral_amount = new_user_position
+        .collateral_amount
+        .safe_add(user_position.collateral_amount)?;
+    new_user_position.deposit_fee = new_user_position
+        .deposit_fee
+        .safe_add(user_position.deposit_fee)?;
+    Ok(())
+}
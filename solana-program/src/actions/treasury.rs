@@ -0,0 +1,399 @@
+use crate::{
+    error::ChickenError,
+    math::SafeMath,
+    state::{EpochBudget, FeeRecipient, FeeSplit, Pool, Treasury},
+    MAX_FEE_RECIPIENTS,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::TransferChecked,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::{assert_admin, bps, pool_settled, update_pool_state};
+
+#[derive(Accounts)]
+pub struct InitializeTreasury<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<Treasury>(),
+        seeds = [
+            b"treasury".as_ref(),
+            collateral_mint.key().as_ref(),
+        ],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    #[account(
+        init,
+        payer = admin,
+        associated_token::mint = collateral_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+      seeds = [
+        b"admin".as_ref(),
+      ],
+      bump
+    )]
+    /// CHECK: Admin account
+    pub admin_record: UncheckedAccount<'info>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_treasury(ctx: Context<InitializeTreasury>, epoch_limit: u64) -> Result<()> {
+    assert_admin(&ctx.accounts.admin_record, &ctx.accounts.admin)?;
+    let treasury = &mut ctx.accounts.treasury;
+    treasury.mint = ctx.accounts.collateral_mint.key();
+    treasury.bump = ctx.bumps.treasury;
+    treasury.budget = EpochBudget {
+        limit: epoch_limit,
+        epoch: Clock::get()?.epoch,
+        ..Default::default()
+    };
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetTreasuryLimit<'info> {
+    #[account(mut)]
+    pub treasury: Account<'info, Treasury>,
+    pub admin: Signer<'info>,
+    #[account(
+      seeds = [
+        b"admin".as_ref(),
+      ],
+      bump
+    )]
+    /// CHECK: Admin account
+    pub admin_record: UncheckedAccount<'info>,
+}
+
+/// The new limit applies from the next epoch, so it can't be raised to spend more in this one.
+pub fn set_treasury_limit(ctx: Context<SetTreasuryLimit>, epoch_limit: u64) -> Result<()> {
+    assert_admin(&ctx.accounts.admin_record, &ctx.accounts.admin)?;
+    let budget = &mut ctx.accounts.treasury.budget;
+    budget.roll(Clock::get()?.epoch);
+    budget.pending_limit = Some(epoch_limit);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SweepFeesToTreasury<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    #[account(mut,
+        associated_token::mint = pool.collateral_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_collateral_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut,
+        seeds = [
+            b"treasury".as_ref(),
+            pool.collateral_mint.as_ref(),
+        ],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    #[account(mut,
+        associated_token::mint = pool.collateral_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Anyone can move an ended or fully refunded pool's fees into the treasury for its mint.
+pub fn sweep_fees_to_treasury(ctx: Context<SweepFeesToTreasury>) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
+    let pool_info = ctx.accounts.pool.to_account_info();
+    let pool = &mut ctx.accounts.pool;
+    update_pool_state(pool, current_slot)?;
+    if !pool_settled(pool) {
+        return err!(ChickenError::PoolNotEnded);
+    }
+    let fee_amount = pool.fee_amount;
+    if fee_amount == 0 {
+        return err!(ChickenError::NothingToSweep);
+    }
+    pool.fee_amount = 0;
+    let treasury = &mut ctx.accounts.treasury;
+    treasury.total_collected = treasury.total_collected.safe_add(fee_amount)?;
+    anchor_spl::token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.pool_collateral_token_account.to_account_info(),
+                to: ctx.accounts.treasury_token_account.to_account_info(),
+                authority: pool_info,
+                mint: ctx.accounts.collateral_mint.to_account_info(),
+            },
+            &[&[
+                b"pool".as_ref(),
+                pool.pool_id.as_ref(),
+                pool.creator.as_ref(),
+                &[pool.bump],
+            ]],
+        ),
+        fee_amount,
+        ctx.accounts.collateral_mint.decimals,
+    )?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFromTreasury<'info> {
+    #[account(mut,
+        seeds = [
+            b"treasury".as_ref(),
+            treasury.mint.as_ref(),
+        ],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    #[account(mut,
+        associated_token::mint = treasury.mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut,
+        token::mint = treasury.mint,
+        token::token_program = token_program
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub admin: Signer<'info>,
+    #[account(
+      seeds = [
+        b"admin".as_ref(),
+      ],
+      bump
+    )]
+    /// CHECK: Admin account
+    pub admin_record: UncheckedAccount<'info>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Spending is capped by the treasury's epoch budget.
+pub fn withdraw_from_treasury(ctx: Context<WithdrawFromTreasury>, amount: u64) -> Result<()> {
+    assert_admin(&ctx.accounts.admin_record, &ctx.accounts.admin)?;
+    let epoch = Clock::get()?.epoch;
+    let treasury_info = ctx.accounts.treasury.to_account_info();
+    let treasury = &mut ctx.accounts.treasury;
+    treasury.budget.roll(epoch);
+    treasury.budget.spend(amount)?;
+    anchor_spl::token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.treasury_token_account.to_account_info(),
+                to: ctx.accounts.recipient_token_account.to_account_info(),
+                authority: treasury_info,
+                mint: ctx.accounts.collateral_mint.to_account_info(),
+            },
+            &[&[
+                b"treasury".as_ref(),
+                treasury.mint.as_ref(),
+                &[treasury.bump],
+            ]],
+        ),
+        amount,
+        ctx.accounts.collateral_mint.decimals,
+    )?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetFeeSplit<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + std::mem::size_of::<FeeSplit>(),
+        seeds = [
+            b"fee_split".as_ref(),
+            collateral_mint.key().as_ref(),
+        ],
+        bump
+    )]
+    pub fee_split: Account<'info, FeeSplit>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+      seeds = [
+        b"admin".as_ref(),
+      ],
+      bump
+    )]
+    /// CHECK: Admin account
+    pub admin_record: UncheckedAccount<'info>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+/// A changed `epoch_limit` applies from the next epoch, like `set_treasury_limit`.
+pub fn set_fee_split(
+    ctx: Context<SetFeeSplit>,
+    recipients: Vec<FeeRecipient>,
+    epoch_limit: u64,
+) -> Result<()> {
+    assert_admin(&ctx.accounts.admin_record, &ctx.accounts.admin)?;
+    let total_bps = recipients.iter().try_fold(0u32, |total, recipient| {
+        total.safe_add(recipient.bps as u32)
+    })?;
+    if recipients.is_empty() || recipients.len() > MAX_FEE_RECIPIENTS || total_bps != 10_000 {
+        return err!(ChickenError::InvalidFeeSplit);
+    }
+    let epoch = Clock::get()?.epoch;
+    let fee_split = &mut ctx.accounts.fee_split;
+    if fee_split.mint == Pubkey::default() {
+        fee_split.budget = EpochBudget {
+            limit: epoch_limit,
+            epoch,
+            ..Default::default()
+        };
+    } else {
+        fee_split.budget.roll(epoch);
+        fee_split.budget.pending_limit = Some(epoch_limit);
+    }
+    fee_split.mint = ctx.accounts.collateral_mint.key();
+    fee_split.bump = ctx.bumps.fee_split;
+    fee_split.recipient_count = recipients.len() as u8;
+    fee_split.recipients = [FeeRecipient::default(); MAX_FEE_RECIPIENTS];
+    fee_split.recipients[..recipients.len()].copy_from_slice(&recipients);
+    Ok(())
+}
+
+/// Pays out the treasury according to its `FeeSplit`. `remaining_accounts` holds one token
+/// account per recipient, in the order of the split.
+#[derive(Accounts)]
+pub struct DistributeTreasury<'info> {
+    #[account(
+        seeds = [
+            b"treasury".as_ref(),
+            treasury.mint.as_ref(),
+        ],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    #[account(mut,
+        associated_token::mint = treasury.mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut,
+        seeds = [
+            b"fee_split".as_ref(),
+            treasury.mint.as_ref(),
+        ],
+        bump = fee_split.bump
+    )]
+    pub fee_split: Account<'info, FeeSplit>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Anyone can distribute the treasury, up to the split's own epoch budget. The admin's
+/// withdrawal budget is left alone.
+pub fn distribute_treasury<'info>(
+    ctx: Context<'_, '_, 'info, 'info, DistributeTreasury<'info>>,
+) -> Result<()> {
+    let epoch = Clock::get()?.epoch;
+    let treasury_info = ctx.accounts.treasury.to_account_info();
+    let fee_split = &mut ctx.accounts.fee_split;
+    let recipients = fee_split.recipients;
+    let recipients = &recipients[..fee_split.recipient_count as usize];
+    if ctx.remaining_accounts.len() != recipients.len() {
+        return err!(ChickenError::InvalidFeeSplitAccounts);
+    }
+    let balance = ctx.accounts.treasury_token_account.amount;
+    if balance == 0 {
+        return err!(ChickenError::NothingToSweep);
+    }
+    fee_split.budget.roll(epoch);
+    let amount = balance.min(fee_split.budget.remaining());
+    if amount == 0 {
+        return err!(ChickenError::TreasuryLimitExceeded);
+    }
+    fee_split.budget.spend(amount)?;
+
+    let shares = recipients
+        .iter()
+        .map(|recipient| bps(amount, recipient.bps))
+        .collect::<Result<Vec<u64>>>()?;
+    let remainder = amount.safe_sub(shares.iter().sum())?;
+    let (mint, bump) = (ctx.accounts.treasury.mint, ctx.accounts.treasury.bump);
+    let signer_seeds: &[&[u8]] = &[b"treasury".as_ref(), mint.as_ref(), &[bump]];
+    for (index, (recipient, info)) in recipients.iter().zip(ctx.remaining_accounts).enumerate() {
+        if info.data_is_empty() {
+            return err!(ChickenError::FeeRecipientAccountMissing);
+        }
+        let token_account = InterfaceAccount::<TokenAccount>::try_from(info)?;
+        if token_account.owner != recipient.owner || token_account.mint != mint {
+            return err!(ChickenError::InvalidFeeSplitAccounts);
+        }
+        let share = if index == 0 {
+            shares[index].safe_add(remainder)?
+        } else {
+            shares[index]
+        };
+        if share == 0 {
+            continue;
+        }
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.treasury_token_account.to_account_info(),
+                    to: info.clone(),
+                    authority: treasury_info.clone(),
+                    mint: ctx.accounts.collateral_mint.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            share,
+            ctx.accounts.collateral_mint.decimals,
+        )?;
+    }
+    Ok(())
+}
+
+impl EpochBudget {
+    /// Resets the spending counter on the first call in a new epoch and applies a limit queued
+    /// in an earlier one.
+    pub fn roll(&mut self, epoch: u64) {
+        if self.epoch != epoch {
+            self.epoch = epoch;
+            self.spent = 0;
+            if let Some(limit) = self.pending_limit.take() {
+                self.limit = limit;
+            }
+        }
+    }
+
+    pub fn remaining(&self) -> u64 {
+        self.limit.saturating_sub(self.spent)
+    }
+
+    pub fn spend(&mut self, amount: u64) -> Result<()> {
+        let spent = self.spent.safe_add(amount)?;
+        if spent > self.limit {
+            return err!(ChickenError::TreasuryLimitExceeded);
+        }
+        self.spent = spent;
+        Ok(())
+    }
+}
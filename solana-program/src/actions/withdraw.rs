@@ -1,8 +1,20 @@
-use super::{assert_pool_withdrawable, bps, update_pool_state};
-use crate::state::{Pool, PoolMode, UserPosition};
+use super::{
+    accrue_season_points, assert_pool_withdrawable, bps, bps_up, burn_position_receipt,
+    close_position_receipt, current_phase, in_sudden_death_window, missed_check_in,
+    pay_slashed_bond, record_exit, season_points, sudden_death_return, sudden_death_window_exit,
+    update_pool_state,
+};
+use crate::{
+    error::ChickenError,
+    events::PositionExited,
+    math::SafeMath,
+    state::{
+        PlayerProfile, Pool, PoolMode, PoolState, Season, SeasonEntry, UserPosition, WeightScheme,
+    },
+};
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    token_2022::TransferChecked,
+    token_2022::{Token2022, TransferChecked},
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
@@ -22,7 +34,6 @@ pub struct Withdraw<'info> {
     pub pool_collateral_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(mut,
         token::mint = pool.collateral_mint,
-        token::authority = user,
         token::token_program = token_program
     )]
     pub user_collateral_token_account: InterfaceAccount<'info, TokenAccount>,
@@ -30,71 +41,210 @@ pub struct Withdraw<'info> {
         seeds = [
             b"user_position".as_ref(),
             pool.key().as_ref(),
-            user.key().as_ref(),
+            user_position.owner.as_ref(),
         ],
         bump,
-        constraint = user_position.owner == user.key(),
-        constraint = user_position.pool == pool.key(),
-        close = payer
+        constraint = user_position.pool == pool.key()
     )]
     pub user_position: Account<'info, UserPosition>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + std::mem::size_of::<PlayerProfile>(),
+        seeds = [
+            b"player_profile".as_ref(),
+            user_position.owner.as_ref(),
+        ],
+        bump
+    )]
+    pub player_profile: Account<'info, PlayerProfile>,
     pub collateral_mint: InterfaceAccount<'info, Mint>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
+    #[account(mut)]
+    pub receipt_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(mut,
+        token::mint = receipt_mint,
+        token::authority = user,
+        token::token_program = receipt_token_program
+    )]
+    pub user_receipt_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub receipt_token_program: Option<Program<'info, Token2022>>,
+    #[account(mut,
+        constraint = season.collateral_mint == pool.collateral_mint
+    )]
+    pub season: Option<Box<Account<'info, Season>>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + std::mem::size_of::<SeasonEntry>(),
+        seeds = [
+            b"season_entry".as_ref(),
+            season.as_ref().map(|season| season.key()).unwrap_or_default().as_ref(),
+            user_position.owner.as_ref(),
+        ],
+        bump
+    )]
+    pub season_entry: Option<Box<Account<'info, SeasonEntry>>>,
+    #[account(mut,
+        associated_token::mint = pool.collateral_mint,
+        associated_token::authority = season,
+        associated_token::token_program = token_program
+    )]
+    pub season_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: whoever funded the position gets its rent back, required when a delegate withdraws
+    #[account(mut, address = user_position.payer)]
+    pub rent_receiver: Option<UncheckedAccount<'info>>,
 }
 
-pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+pub fn withdraw(ctx: Context<Withdraw>, min_out: u64) -> Result<()> {
     let current_slot = Clock::get()?.slot;
     let pool_info = ctx.accounts.pool.to_account_info();
     let pool = &mut ctx.accounts.pool;
     update_pool_state(pool, current_slot)?;
     assert_pool_withdrawable(pool)?;
 
-    let total_deposits = ctx.accounts.pool_collateral_token_account.amount
-        - pool.fee_amount
-        - pool.collateral_amount;
-
-    let user_position = &mut ctx.accounts.user_position;
-
-    let return_amount = match pool.mode {
-        PoolMode::LastOutWinner => {
-            if pool.users == 1 {
-                (
-                    user_position.deposit_amount + pool.collateral_amount,
-                    pool.collateral_amount,
-                )
-            } else {
-                (user_position.deposit_amount, 0u64)
+    let user = ctx.accounts.user.key();
+    let recipient = ctx.accounts.user_collateral_token_account.owner;
+    let user_position = &ctx.accounts.user_position;
+    let (pool_id, creator, bump) = (pool.pool_id, pool.creator, pool.bump);
+    let signer_seeds: &[&[u8]] = &[
+        b"pool".as_ref(),
+        pool_id.as_ref(),
+        creator.as_ref(),
+        &[bump],
+    ];
+    let rent_receiver = match &ctx.accounts.rent_receiver {
+        Some(rent_receiver) => rent_receiver.to_account_info(),
+        None => ctx.accounts.payer.to_account_info(),
+    };
+    match user_position.receipt_mint {
+        Some(receipt_mint_key) => {
+            let (Some(receipt_mint), Some(user_receipt_token_account), Some(receipt_token_program)) = (
+                &ctx.accounts.receipt_mint,
+                &ctx.accounts.user_receipt_token_account,
+                &ctx.accounts.receipt_token_program,
+            ) else {
+                return err!(ChickenError::PositionReceiptRequired);
+            };
+            if receipt_mint.key() != receipt_mint_key || user_receipt_token_account.amount != 1 {
+                return err!(ChickenError::InvalidPositionReceipt);
             }
+            if recipient != user {
+                return err!(ChickenError::Unauthorized);
+            }
+            burn_position_receipt(
+                receipt_mint,
+                user_receipt_token_account,
+                ctx.accounts.user.to_account_info(),
+                &[],
+                receipt_token_program,
+            )?;
+            close_position_receipt(
+                pool_info.clone(),
+                signer_seeds,
+                receipt_mint,
+                user_receipt_token_account,
+                ctx.accounts.user.to_account_info(),
+                rent_receiver.clone(),
+                receipt_token_program,
+            )?;
         }
-        PoolMode::TimeBased => {
-            let total_slots = pool.end_time - pool.start_time;
-            let slots_passed = current_slot - pool.start_time;
+        None => {
+            if user_position.owner != user {
+                let Some(delegate) = user_position
+                    .delegate
+                    .filter(|delegate| delegate.authority == user)
+                else {
+                    return err!(ChickenError::Unauthorized);
+                };
+                if current_slot > delegate.expiry_slot {
+                    return err!(ChickenError::DelegateExpired);
+                }
+                if ctx.accounts.rent_receiver.is_none() {
+                    return err!(ChickenError::RentReceiverRequired);
+                }
+            }
+            // delegates may only pay out to the owner
+            if recipient != user_position.owner {
+                return err!(ChickenError::Unauthorized);
+            }
+        }
+    }
 
-            let time_in_pool =
-                (user_position.deposit_time - pool.start_time - slots_passed) / total_slots;
-            let curve = time_in_pool ^ 3;
-            let collateral_refund =
-                user_position.collateral_amount - (user_position.collateral_amount * curve);
-            let user_deposit_percentage = user_position.deposit_amount / total_deposits;
-            let pool_collateral_amount = pool.collateral_amount - collateral_refund;
-            let user_collateral_amount = pool_collateral_amount * user_deposit_percentage;
-            let reward_curve = time_in_pool ^ 2;
-            let rewards = user_collateral_amount - (user_collateral_amount * reward_curve);
-            (
-                user_position.deposit_amount + collateral_refund + rewards,
-                collateral_refund + rewards,
-            )
+    // inside a sudden-death window the position stays open until the end is revealed
+    let window_exit = in_sudden_death_window(pool, current_slot);
+    if window_exit {
+        let final_amount =
+            sudden_death_window_exit(pool, &mut ctx.accounts.user_position, current_slot)?;
+        if final_amount < min_out {
+            return err!(ChickenError::SlippageExceeded);
         }
-    };
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.pool_collateral_token_account.to_account_info(),
+                    to: ctx.accounts.user_collateral_token_account.to_account_info(),
+                    authority: pool_info,
+                    mint: ctx.accounts.collateral_mint.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            final_amount,
+            ctx.accounts.collateral_mint.decimals,
+        )?;
+        return Ok(());
+    }
+
+    let (final_amount, fee) = process_withdraw(pool, user_position, current_slot)?;
+    if final_amount < min_out {
+        return err!(ChickenError::SlippageExceeded);
+    }
+    pay_slashed_bond(pool, user_position)?;
+    let won = record_exit(
+        &mut ctx.accounts.player_profile,
+        ctx.bumps.player_profile,
+        user_position,
+        final_amount,
+        fee,
+        current_slot,
+        pool.state == PoolState::Refunding,
+    );
+
+    if let (Some(season), Some(season_entry), Some(season_token_account)) = (
+        &mut ctx.accounts.season,
+        &mut ctx.accounts.season_entry,
+        &ctx.accounts.season_token_account,
+    ) {
+        if current_slot >= season.start_slot && current_slot <= season.end_slot {
+            // only a stake at risk scores, and only for the part of the hold inside the season
+            if user_position.collateral_amount > 0 && pool.state != PoolState::Refunding {
+                let held_since = user_position.deposit_time.max(season.start_slot);
+                let points = season_points(current_slot.saturating_sub(held_since), won);
+                accrue_season_points(season, season_entry, user_position.owner, points);
+            }
+            // the season's cut comes out of the protocol's share of the fee
+            let prize = bps(fee, season.fee_share_bps)?;
+            pool.fee_amount = pool.fee_amount.safe_sub(prize)?;
+            season.prize_pool = season.prize_pool.safe_add(prize)?;
+            anchor_spl::token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.pool_collateral_token_account.to_account_info(),
+                        to: season_token_account.to_account_info(),
+                        authority: pool_info.clone(),
+                        mint: ctx.accounts.collateral_mint.to_account_info(),
+                    },
+                    &[signer_seeds],
+                ),
+                prize,
+                ctx.accounts.collateral_mint.decimals,
+            )?;
+        }
+    }
 
-    let fee = bps(return_amount.0, pool.withdraw_fee_bps)?;
-    let final_amount = return_amount.0 - fee;
-    pool.withdrawn += final_amount;
-    pool.users -= 1;
-    pool.fee_amount += fee;
-    pool.collateral_amount -= return_amount.1;
-    pool.withdrawn += return_amount.0 - return_amount.1;
     anchor_spl::token_interface::transfer_checked(
         CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
@@ -104,16 +254,212 @@ pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
                 authority: pool_info,
                 mint: ctx.accounts.collateral_mint.to_account_info(),
             },
-            &[&[
-                b"pool".as_ref(),
-                pool.pool_id.as_ref(),
-                pool.creator.as_ref(),
-                &[pool.bump],
-            ]],
+            &[signer_seeds],
         ),
         final_amount,
         ctx.accounts.collateral_mint.decimals,
     )?;
 
-    Ok(())
+    ctx.accounts.user_position.close(rent_receiver)
+}
+
+/// Runs the payout rules for `user_position` against `pool` and books the exit in the pool
+/// accounting. Returns the amount owed to the owner and the withdraw fee taken from it.
+pub fn process_withdraw(
+    pool: &mut Pool,
+    user_position: &UserPosition,
+    current_slot: u64,
+) -> Result<(u64, u64)> {
+    let (final_amount, fee) = book_withdraw(pool, user_position, current_slot)?;
+    emit!(PositionExited {
+        pool: user_position.pool,
+        owner: user_position.owner,
+        exit_index: pool.exits,
+        amount: final_amount,
+        slot: current_slot,
+    });
+    pool.exits = pool.exits.safe_add(1)?;
+    Ok((final_amount, fee))
+}
+
+/// The accounting half of `process_withdraw`, also run against a copy of the pool for quotes.
+pub fn book_withdraw(
+    pool: &mut Pool,
+    user_position: &UserPosition,
+    current_slot: u64,
+) -> Result<(u64, u64)> {
+    pool.total_principal = pool
+        .total_principal
+        .safe_sub(user_position.deposit_amount)?;
+    if pool.state == PoolState::Refunding {
+        let mut refund = user_position
+            .deposit_amount
+            .safe_add(user_position.collateral_amount)?
+            .safe_add(user_position.deposit_fee)?;
+        if pool.users == 1 {
+            // the last one out takes collateral forfeited before the game was called off
+            refund = refund.safe_add(pool.total_principal)?.safe_add(
+                pool.collateral_amount
+                    .safe_sub(user_position.collateral_amount)?,
+            )?;
+            pool.collateral_amount = user_position.collateral_amount;
+            pool.total_principal = 0;
+        }
+        pool.collateral_amount = pool
+            .collateral_amount
+            .safe_sub(user_position.collateral_amount)?;
+        pool.fee_amount = pool.fee_amount.safe_sub(user_position.deposit_fee)?;
+        pool.users = pool.users.safe_sub(1)?;
+        pool.withdrawn = pool.withdrawn.safe_add(refund)?;
+        return Ok((refund, 0));
+    }
+    let return_amount = match pool.mode {
+        _ if pool.sudden_death.is_some() => sudden_death_return(pool, user_position, current_slot)?,
+        PoolMode::LastOutWinner => {
+            if pool.users == 1 {
+                (
+                    user_position
+                        .deposit_amount
+                        .safe_add(pool.collateral_amount)?,
+                    pool.collateral_amount,
+                )
+            } else if let Some(phase) = current_phase(pool, current_slot) {
+                // only the phase penalty is forfeited, the rest of the collateral comes back
+                let refund = user_position.collateral_amount.safe_sub(bps_up(
+                    user_position.collateral_amount,
+                    phase.exit_penalty_bps,
+                )?)?;
+                (user_position.deposit_amount.safe_add(refund)?, refund)
+            } else {
+                (user_position.deposit_amount, 0u64)
+            }
+        }
+        PoolMode::KingOfTheHill {
+            winner_share_bps, ..
+        } => {
+            if pool.state == PoolState::Ended && user_position.owner == pool.last_depositor {
+                let prize = bps(pool.collateral_amount, winner_share_bps)?;
+                // whatever the winner does not take goes to the protocol
+                pool.fee_amount = pool
+                    .fee_amount
+                    .safe_add(pool.collateral_amount.safe_sub(prize)?)?;
+                pool.collateral_amount = prize;
+                pool.last_depositor = Pubkey::default();
+                (user_position.deposit_amount.safe_add(prize)?, prize)
+            } else {
+                // leaving early gives up the crown
+                if user_position.owner == pool.last_depositor {
+                    pool.last_depositor = Pubkey::default();
+                }
+                if pool.users == 1 && pool.last_depositor == Pubkey::default() {
+                    pool.fee_amount = pool.fee_amount.safe_add(pool.collateral_amount)?;
+                    pool.collateral_amount = 0;
+                }
+                (user_position.deposit_amount, 0u64)
+            }
+        }
+        PoolMode::LastNOut { winners, split } => {
+            // the pot is fixed when the winners are decided so the exit order cannot change it
+            if pool.winner_count == 0 && pool.users <= winners as u32 {
+                pool.winner_count = pool.users;
+                pool.winner_pot = pool.collateral_amount;
+                // principal of the winners, the exiting position included
+                pool.winner_weight = pool
+                    .total_principal
+                    .safe_add(user_position.deposit_amount)?;
+            }
+            let share = if pool.winner_count == 0 {
+                0
+            } else if pool.users == 1 {
+                pool.collateral_amount
+            } else {
+                match split {
+                    WeightScheme::Even => pool.winner_pot / pool.winner_count as u64,
+                    WeightScheme::ByDeposit => (pool.winner_pot as u128
+                        * user_position.deposit_amount as u128)
+                        .checked_div(pool.winner_weight as u128)
+                        .unwrap_or_default() as u64,
+                }
+            };
+            (user_position.deposit_amount.safe_add(share)?, share)
+        }
+        PoolMode::Survival { .. } => {
+            let survived = pool.state == PoolState::Ended
+                && !user_position.eliminated
+                && !missed_check_in(pool, user_position, current_slot);
+            let share = if !survived {
+                0
+            } else if pool.users == 1 {
+                pool.collateral_amount
+            } else {
+                // pro-rata of what is left, so the order survivors leave in does not matter
+                (pool.collateral_amount as u128 * user_position.collateral_amount as u128)
+                    .checked_div(pool.open_collateral as u128)
+                    .unwrap_or_default() as u64
+            };
+            if !user_position.eliminated {
+                pool.open_collateral = pool
+                    .open_collateral
+                    .safe_sub(user_position.collateral_amount)?;
+            }
+            if !survived && pool.users == 1 {
+                pool.fee_amount = pool.fee_amount.safe_add(pool.collateral_amount)?;
+                pool.collateral_amount = 0;
+            }
+            (user_position.deposit_amount.safe_add(share)?, share)
+        }
+        PoolMode::TimeBased => {
+            let held = current_slot.min(pool.end_time).saturating_sub(
+                user_position
+                    .deposit_time
+                    .max(pool.start_time)
+                    .min(pool.end_time),
+            );
+            let vested = pool
+                .payout_curve
+                .evaluate(held, pool.end_time.saturating_sub(pool.start_time));
+            let refund = bps(user_position.collateral_amount, vested as u16)?;
+            // collateral forfeited by earlier exits is shared out on the same curve
+            let forfeited = pool.collateral_amount.safe_sub(pool.open_collateral)?;
+            let rewards = bps(
+                (forfeited as u128 * user_position.collateral_amount as u128)
+                    .checked_div(pool.open_collateral as u128)
+                    .unwrap_or_default() as u64,
+                vested as u16,
+            )?;
+            pool.open_collateral = pool
+                .open_collateral
+                .safe_sub(user_position.collateral_amount)?;
+            let vested_collateral = refund.safe_add(rewards)?;
+            if pool.users == 1 {
+                // nobody is left to claim what remains
+                pool.fee_amount = pool
+                    .fee_amount
+                    .safe_add(pool.collateral_amount.safe_sub(vested_collateral)?)?;
+                pool.collateral_amount = vested_collateral;
+            }
+            (
+                user_position.deposit_amount.safe_add(vested_collateral)?,
+                vested_collateral,
+            )
+        }
+    };
+
+    let (mut gross, mut collateral) = return_amount;
+    if pool.users == 1 {
+        // the last one out takes whatever rounding left behind
+        gross = gross
+            .safe_add(pool.total_principal)?
+            .safe_add(pool.collateral_amount.safe_sub(collateral)?)?;
+        collateral = pool.collateral_amount;
+        pool.total_principal = 0;
+    }
+    let fee = bps_up(gross, pool.withdraw_fee_bps)?;
+    let final_amount = gross.safe_sub(fee)?;
+    pool.withdrawn = pool.withdrawn.safe_add(final_amount)?;
+    pool.users = pool.users.safe_sub(1)?;
+    pool.fee_amount = pool.fee_amount.safe_add(fee)?;
+    pool.collateral_amount = pool.collateral_amount.safe_sub(collateral)?;
+    pool.withdrawn = pool.withdrawn.safe_add(gross.safe_sub(collateral)?)?;
+    Ok((final_amount, fee))
 }
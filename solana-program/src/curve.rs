@@ -0,0 +1,89 @@
+use crate::state::{CurvePoint, PayoutCurve};
+
+pub const CURVE_ONE: u64 = 10_000;
+
+impl PayoutCurve {
+    /// Fraction of the payout earned after `elapsed` of `duration` slots, in bps. Every curve
+    /// starts at zero, never decreases and never exceeds `CURVE_ONE`.
+    pub fn evaluate(&self, elapsed: u64, duration: u64) -> u64 {
+        if duration == 0 || elapsed >= duration {
+            return self.end_value();
+        }
+        let elapsed = elapsed.min(duration) as u128;
+        let duration = duration as u128;
+        let one = CURVE_ONE as u128;
+        let x = elapsed * one / duration;
+        let value = match self {
+            Self::Linear => x,
+            Self::Quadratic => x * x / one,
+            Self::Cubic => x * x * x / (one * one),
+            Self::ExponentialDecay { half_life } => {
+                if *half_life == 0 {
+                    return CURVE_ONE;
+                }
+                // scaled so the curve reaches `CURVE_ONE` at the end like every other curve
+                let half_life = *half_life as u128;
+                decay(elapsed, half_life) * one / decay(duration, half_life)
+            }
+            Self::PiecewiseLinear { points, len } => {
+                interpolate(&points[..(*len as usize).min(points.len())], x)
+            }
+        };
+        value.min(one) as u64
+    }
+
+    fn end_value(&self) -> u64 {
+        match self {
+            Self::PiecewiseLinear { points, len } => interpolate(
+                &points[..(*len as usize).min(points.len())],
+                CURVE_ONE as u128,
+            ) as u64,
+            _ => CURVE_ONE,
+        }
+    }
+
+    /// Piecewise tables must have increasing `x`, non-decreasing `y` and stay within bounds.
+    pub fn is_valid(&self) -> bool {
+        let Self::PiecewiseLinear { points, len } = self else {
+            return true;
+        };
+        let Some(points) = points.get(..*len as usize) else {
+            return false;
+        };
+        !points.is_empty()
+            && points
+                .iter()
+                .all(|point| point.x <= CURVE_ONE as u16 && point.y <= CURVE_ONE as u16)
+            && points
+                .windows(2)
+                .all(|pair| pair[0].x < pair[1].x && pair[0].y <= pair[1].y)
+    }
+}
+
+/// `1 - 2^-(t/h)` in bps, interpolated linearly between whole halvings.
+fn decay(elapsed: u128, half_life: u128) -> u128 {
+    let one = CURVE_ONE as u128;
+    let halvings = elapsed / half_life;
+    if halvings >= 64 {
+        return one;
+    }
+    let remainder = elapsed % half_life;
+    one - (one >> halvings) * (2 * half_life - remainder) / (2 * half_life)
+}
+
+/// Walks the table from an implicit `(0, 0)` and holds the last `y` past the final point.
+fn interpolate(points: &[CurvePoint], x: u128) -> u128 {
+    let mut previous = CurvePoint::default();
+    for point in points {
+        let (x0, y0) = (previous.x as u128, previous.y as u128);
+        let (x1, y1) = (point.x as u128, point.y as u128);
+        if x <= x1 {
+            if x1 == x0 {
+                return y1;
+            }
+            return y0 + (y1 - y0) * (x - x0) / (x1 - x0);
+        }
+        previous = *point;
+    }
+    previous.y as u128
+}
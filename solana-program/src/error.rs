@@ -17,4 +17,108 @@ pub enum ChickenError {
     PoolNotEnded,
     #[msg("Unauthorized")]
     Unauthorized,
+    #[msg("Invalid settlement accounts")]
+    InvalidSettleAccounts,
+    #[msg("Pool state unchanged")]
+    PoolStateUnchanged,
+    #[msg("Position receipt accounts required")]
+    PositionReceiptRequired,
+    #[msg("Invalid position receipt")]
+    InvalidPositionReceipt,
+    #[msg("Share mint required")]
+    ShareMintRequired,
+    #[msg("Share tokens are not supported for this pool")]
+    SharesUnsupported,
+    #[msg("Pool uses share tokens")]
+    SharePool,
+    #[msg("Invalid share amount")]
+    InvalidShareAmount,
+    #[msg("Position is not transferable")]
+    PositionNotTransferable,
+    #[msg("Position delegate expired")]
+    DelegateExpired,
+    #[msg("Exit condition not met")]
+    ExitConditionNotMet,
+    #[msg("Season is not over")]
+    SeasonNotOver,
+    #[msg("No season prize to claim")]
+    NoSeasonPrize,
+    #[msg("Invalid pool mode parameters")]
+    InvalidPoolMode,
+    #[msg("Pool end has not been revealed")]
+    EndNotRevealed,
+    #[msg("Pool end already revealed")]
+    EndAlreadyRevealed,
+    #[msg("Reveal window has not passed")]
+    RevealTooEarly,
+    #[msg("Reveal does not match commitment")]
+    InvalidReveal,
+    #[msg("Position has been eliminated")]
+    PositionEliminated,
+    #[msg("Check-in interval missed")]
+    MissedCheckIn,
+    #[msg("Position has not missed a check-in")]
+    CheckInNotMissed,
+    #[msg("Invalid pool phases")]
+    InvalidPoolPhases,
+    #[msg("Deposits are closed in this phase")]
+    DepositsClosed,
+    #[msg("Invalid payout curve")]
+    InvalidPayoutCurve,
+    #[msg("Pool is refunding")]
+    PoolRefunding,
+    #[msg("Pool is full")]
+    PoolFull,
+    #[msg("Payout is below the minimum accepted")]
+    SlippageExceeded,
+    #[msg("Pool fees exceed the accepted maximum")]
+    FeeLimitExceeded,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Math underflow")]
+    MathUnderflow,
+    #[msg("Pool must start before it ends")]
+    InvalidPoolDuration,
+    #[msg("Pool start is in the past")]
+    PoolStartInPast,
+    #[msg("Collateral bps above 10000")]
+    InvalidCollateralBps,
+    #[msg("Max deposit is below the minimum deposit")]
+    MaxDepositBelowMinimum,
+    #[msg("Total deposit limit is below the minimum deposit")]
+    DepositLimitBelowMinimum,
+    #[msg("Keeper bounty above the protocol maximum")]
+    InvalidKeeperBounty,
+    #[msg("Invalid user limits")]
+    InvalidUserLimits,
+    #[msg("Pool still has users")]
+    PoolHasUsers,
+    #[msg("Nothing to sweep")]
+    NothingToSweep,
+    #[msg("Treasury epoch limit exceeded")]
+    TreasuryLimitExceeded,
+    #[msg("Fee split needs 1 to 4 recipients whose bps sum to 10000")]
+    InvalidFeeSplit,
+    #[msg("Invalid fee split accounts")]
+    InvalidFeeSplitAccounts,
+    #[msg("Fee recipient token account is missing")]
+    FeeRecipientAccountMissing,
+    #[msg("Rent receiver account required")]
+    RentReceiverRequired,
+    #[msg("Exit order was placed on a closed position")]
+    StaleExitOrder,
+    #[msg("Season fee share above 10000 bps")]
+    InvalidSeasonFeeShare,
+    #[msg("Winners have already been decided")]
+    WinnersDecided,
+    #[msg("Invalid king-of-the-hill parameters")]
+    InvalidKingOfTheHill,
+    #[msg("Last-n-out pools need at least one winner")]
+    InvalidLastNOut,
+    #[msg("Survival check-in interval must be positive")]
+    InvalidSurvivalInterval,
+    #[msg("Invalid sudden-death parameters")]
+    InvalidSuddenDeath,
+    #[msg("Position receipts are not supported for this pool")]
+    ReceiptsUnsupported,
 }
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::state::PoolState;
+
+#[event]
+pub struct PoolStateChanged {
+    pub pool: Pubkey,
+    pub previous: PoolState,
+    pub current: PoolState,
+    pub slot: u64,
+}
+
+/// Result of `audit_pool`. `balanced` is whether the vault holds exactly the principal,
+/// collateral and fees the pool has booked.
+#[event]
+pub struct PoolAudited {
+    pub pool: Pubkey,
+    pub vault_amount: u64,
+    pub total_principal: u64,
+    pub collateral_amount: u64,
+    pub fee_amount: u64,
+    pub balanced: bool,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PositionExited {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub exit_index: u32,
+    pub amount: u64,
+    pub slot: u64,
+}
@@ -1,12 +1,25 @@
 use actions::*;
 use anchor_lang::prelude::*;
+use state::{ExitCondition, FeeRecipient, PositionDelegate};
+pub mod curve;
 pub mod error;
+pub mod events;
+pub mod math;
 
 pub mod actions;
 pub mod state;
 
 pub const DEPOSIT_FEE_BPS: u16 = 10;
 pub const WITHDRAW_FEE_BPS: u16 = 10;
+pub const SETTLE_BOUNTY_BPS: u16 = 1_000;
+pub const MAX_KEEPER_BOUNTY_BPS: u16 = 1_000;
+pub const SEASON_LEADERBOARD_SIZE: usize = 10;
+pub const SEASON_WIN_POINTS: u64 = 1_000;
+pub const SUDDEN_DEATH_REVEAL_TIMEOUT: u64 = 256;
+pub const SUDDEN_DEATH_BUCKETS: usize = 32;
+pub const MAX_POOL_PHASES: usize = 8;
+pub const MAX_CURVE_POINTS: usize = 8;
+pub const MAX_FEE_RECIPIENTS: usize = 4;
 
 declare_id!("chknZh1FSSbASjrsFxTVPphCLQqeENFJJ2yTofyk3kB");
 
@@ -18,20 +31,138 @@ pub mod chicken_pool {
         actions::initialize_pool(ctx, args)
     }
 
-    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
-        actions::deposit(ctx, amount)
+    pub fn deposit(
+        ctx: Context<Deposit>,
+        amount: u64,
+        max_fee_bps: u16,
+        max_collateral_bps: u16,
+    ) -> Result<()> {
+        actions::deposit(ctx, amount, max_fee_bps, max_collateral_bps)
     }
 
-    pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
-        actions::withdraw(ctx)
+    pub fn withdraw(ctx: Context<Withdraw>, min_out: u64) -> Result<()> {
+        actions::withdraw(ctx, min_out)
+    }
+
+    pub fn set_position_delegate(
+        ctx: Context<SetPositionDelegate>,
+        delegate: Option<PositionDelegate>,
+    ) -> Result<()> {
+        actions::set_position_delegate(ctx, delegate)
+    }
+
+    pub fn place_exit_order(
+        ctx: Context<PlaceExitOrder>,
+        condition: ExitCondition,
+        tip: u64,
+    ) -> Result<()> {
+        actions::place_exit_order(ctx, condition, tip)
+    }
+
+    pub fn cancel_exit_order(ctx: Context<CancelExitOrder>) -> Result<()> {
+        actions::cancel_exit_order(ctx)
+    }
+
+    pub fn execute_exit_order(ctx: Context<ExecuteExitOrder>) -> Result<()> {
+        actions::execute_exit_order(ctx)
+    }
+
+    pub fn transfer_position(ctx: Context<TransferPosition>, new_owner: Pubkey) -> Result<()> {
+        actions::transfer_position(ctx, new_owner)
+    }
+
+    pub fn deposit_shares(
+        ctx: Context<DepositShares>,
+        amount: u64,
+        max_fee_bps: u16,
+        max_collateral_bps: u16,
+    ) -> Result<()> {
+        actions::deposit_shares(ctx, amount, max_fee_bps, max_collateral_bps)
+    }
+
+    pub fn withdraw_shares(ctx: Context<WithdrawShares>, shares: u64) -> Result<()> {
+        actions::withdraw_shares(ctx, shares)
+    }
+
+    pub fn settle<'info>(ctx: Context<'_, '_, 'info, 'info, Settle<'info>>) -> Result<()> {
+        actions::settle(ctx)
+    }
+
+    pub fn poke_pool(ctx: Context<PokePool>) -> Result<()> {
+        actions::poke_pool(ctx)
     }
 
     pub fn remove_pool(ctx: Context<RemovePool>) -> Result<()> {
         actions::remove_pool(ctx)
     }
 
-    pub fn claim_fees(ctx: Context<ClaimFees>) -> Result<()> {
-        actions::claim_fees(ctx)
+    pub fn reveal_end(ctx: Context<RevealEnd>, secret: [u8; 32], salt: [u8; 32]) -> Result<()> {
+        actions::reveal_end(ctx, secret, salt)
+    }
+
+    pub fn check_in(ctx: Context<CheckIn>) -> Result<()> {
+        actions::check_in(ctx)
+    }
+
+    pub fn eliminate(ctx: Context<Eliminate>) -> Result<()> {
+        actions::eliminate(ctx)
+    }
+
+    pub fn audit_pool(ctx: Context<AuditPool>) -> Result<()> {
+        actions::audit_pool(ctx)
+    }
+
+    pub fn initialize_treasury(ctx: Context<InitializeTreasury>, epoch_limit: u64) -> Result<()> {
+        actions::initialize_treasury(ctx, epoch_limit)
+    }
+
+    pub fn set_treasury_limit(ctx: Context<SetTreasuryLimit>, epoch_limit: u64) -> Result<()> {
+        actions::set_treasury_limit(ctx, epoch_limit)
+    }
+
+    pub fn sweep_fees_to_treasury(ctx: Context<SweepFeesToTreasury>) -> Result<()> {
+        actions::sweep_fees_to_treasury(ctx)
+    }
+
+    pub fn withdraw_from_treasury(ctx: Context<WithdrawFromTreasury>, amount: u64) -> Result<()> {
+        actions::withdraw_from_treasury(ctx, amount)
+    }
+
+    pub fn set_fee_split(
+        ctx: Context<SetFeeSplit>,
+        recipients: Vec<FeeRecipient>,
+        epoch_limit: u64,
+    ) -> Result<()> {
+        actions::set_fee_split(ctx, recipients, epoch_limit)
+    }
+
+    pub fn distribute_treasury<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DistributeTreasury<'info>>,
+    ) -> Result<()> {
+        actions::distribute_treasury(ctx)
+    }
+
+    pub fn sweep_dust(ctx: Context<SweepDust>) -> Result<()> {
+        actions::sweep_dust(ctx)
+    }
+
+    pub fn quote_deposit(ctx: Context<QuoteDeposit>, amount: u64) -> Result<Quote> {
+        actions::quote_deposit(ctx, amount)
+    }
+
+    pub fn quote_withdraw(ctx: Context<QuoteWithdraw>) -> Result<Quote> {
+        actions::quote_withdraw(ctx)
+    }
+
+    pub fn initialize_season(
+        ctx: Context<InitializeSeason>,
+        args: InitializeSeasonArgs,
+    ) -> Result<()> {
+        actions::initialize_season(ctx, args)
+    }
+
+    pub fn claim_season_prize(ctx: Context<ClaimSeasonPrize>) -> Result<()> {
+        actions::claim_season_prize(ctx)
     }
 
     pub fn initialize_admin(ctx: Context<InitializeAdmin>) -> Result<()> {
@@ -0,0 +1,35 @@
+use crate::error::ChickenError;
+use anchor_lang::prelude::*;
+
+/// Checked arithmetic for pool and position accounting. Overflow-checked builds would otherwise
+/// abort the transaction with a panic instead of a program error.
+pub trait SafeMath: Sized {
+    fn safe_add(self, rhs: Self) -> Result<Self>;
+    fn safe_sub(self, rhs: Self) -> Result<Self>;
+    fn safe_mul(self, rhs: Self) -> Result<Self>;
+}
+
+macro_rules! impl_safe_math {
+    ($($t:ty),*) => {
+        $(
+            impl SafeMath for $t {
+                #[inline(always)]
+                fn safe_add(self, rhs: Self) -> Result<Self> {
+                    self.checked_add(rhs).ok_or_else(|| ChickenError::MathOverflow.into())
+                }
+
+                #[inline(always)]
+                fn safe_sub(self, rhs: Self) -> Result<Self> {
+                    self.checked_sub(rhs).ok_or_else(|| ChickenError::MathUnderflow.into())
+                }
+
+                #[inline(always)]
+                fn safe_mul(self, rhs: Self) -> Result<Self> {
+                    self.checked_mul(rhs).ok_or_else(|| ChickenError::MathOverflow.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_safe_math!(u32, u64, u128);
@@ -1,5 +1,10 @@
 use anchor_lang::prelude::*;
 
+use crate::{
+    MAX_CURVE_POINTS, MAX_FEE_RECIPIENTS, MAX_POOL_PHASES, SEASON_LEADERBOARD_SIZE,
+    SUDDEN_DEATH_BUCKETS,
+};
+
 #[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Eq, PartialEq)]
 pub enum PoolState {
     #[default]
@@ -7,13 +12,87 @@ pub enum PoolState {
     Started = 1,
     Ended = 2,
     Removed = 3,
+    Refunding = 4,
 }
 
 #[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Eq, PartialEq)]
 pub enum PoolMode {
     #[default]
-    LastOutWinner = 0,
-    TimeBased = 1,
+    LastOutWinner,
+    TimeBased,
+    /// Every deposit of at least `min_deposit` pushes `end_time` out by `extension`, never past
+    /// `max_end_time`. The last such depositor takes `winner_share_bps` of the collateral pot and
+    /// the rest of the pot is booked as protocol fees.
+    KingOfTheHill {
+        extension: u64,
+        max_end_time: u64,
+        winner_share_bps: u16,
+    },
+    /// Once `users <= winners` everyone still in the pool shares the collateral pot.
+    LastNOut {
+        winners: u8,
+        split: WeightScheme,
+    },
+    /// Players must `check_in` at least every `interval` slots. Survivors at the end share the
+    /// collateral of everyone who missed a heartbeat or left early.
+    Survival {
+        interval: u64,
+    },
+}
+
+/// A point on a piecewise-linear curve, both axes in bps.
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Eq, PartialEq)]
+pub struct CurvePoint {
+    pub x: u16,
+    pub y: u16,
+}
+
+/// How much of a `TimeBased` position's collateral has vested as it is held.
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Eq, PartialEq)]
+pub enum PayoutCurve {
+    #[default]
+    Linear,
+    Quadratic,
+    Cubic,
+    ExponentialDecay {
+        half_life: u64,
+    },
+    PiecewiseLinear {
+        points: [CurvePoint; MAX_CURVE_POINTS],
+        len: u8,
+    },
+}
+
+/// Hidden end committed by the creator. The real end falls on one of the bucket boundaries in
+/// `[earliest_end, end_time]` and is only known once `reveal_end` runs. `exit_buckets` holds the
+/// collateral of the positions that left inside the window, tallied per bucket.
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Eq, PartialEq)]
+pub struct SuddenDeath {
+    pub commitment: [u8; 32],
+    pub earliest_end: u64,
+    pub bond: u64,
+    pub revealed_end: Option<u64>,
+    pub window_exits: u32,
+    pub at_risk_collateral: u64,
+    pub pot: u64,
+    pub winning_collateral: u64,
+    pub exit_buckets: [u64; SUDDEN_DEATH_BUCKETS],
+}
+
+/// A phase starts `slot_offset` slots after `start_time` and lasts until the next one begins.
+/// Leaving during a phase forfeits `exit_penalty_bps` of the position's collateral.
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Eq, PartialEq)]
+pub struct PoolPhase {
+    pub slot_offset: u64,
+    pub exit_penalty_bps: u16,
+    pub deposit_allowed: bool,
+}
+
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Eq, PartialEq)]
+pub enum WeightScheme {
+    #[default]
+    Even,
+    ByDeposit,
 }
 
 #[account]
@@ -38,6 +117,30 @@ pub struct Pool {
     pub collateral_mint: Pubkey,
     pub max_deposit: Option<u64>,
     pub total_deposit_limit: Option<u64>,
+    pub max_users: Option<u32>,
+    pub keeper_bounty_bps: u16,
+    pub position_receipts: bool,
+    pub share_mint: Option<Pubkey>,
+    pub transferable: bool,
+    pub last_depositor: Pubkey,
+    pub exits: u32,
+    pub winner_count: u32,
+    pub winner_pot: u64,
+    pub winner_weight: u64,
+    pub sudden_death: Option<SuddenDeath>,
+    pub open_collateral: u64,
+    pub phase_count: u8,
+    pub phases: [PoolPhase; MAX_POOL_PHASES],
+    pub payout_curve: PayoutCurve,
+    pub min_users: u32,
+    pub total_principal: u64,
+    pub joined: u32,
+}
+
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Eq, PartialEq)]
+pub struct PositionDelegate {
+    pub authority: Pubkey,
+    pub expiry_slot: u64,
 }
 
 #[account]
@@ -49,4 +152,114 @@ pub struct UserPosition {
     pub deposit_amount: u64,
     pub deposit_time: u64,
     pub withdrawn: bool,
+    pub payer: Pubkey,
+    pub receipt_mint: Option<Pubkey>,
+    pub delegate: Option<PositionDelegate>,
+    pub exit_slot: u64,
+    pub last_check_in: u64,
+    pub eliminated: bool,
+    pub deposit_fee: u64,
+    pub has_exit_order: bool,
+}
+
+#[account]
+#[derive(Debug, Default)]
+pub struct PlayerProfile {
+    pub owner: Pubkey,
+    pub bump: u8,
+    pub pools_joined: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub total_deposited: u64,
+    pub total_won: u64,
+    pub total_fees_paid: u64,
+    pub longest_hold: u64,
+}
+
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Eq, PartialEq)]
+pub struct SeasonLeader {
+    pub player: Pubkey,
+    pub points: u64,
+    pub claimed: bool,
+}
+
+#[account]
+#[derive(Debug, Default)]
+pub struct Season {
+    pub season_id: u64,
+    pub bump: u8,
+    pub collateral_mint: Pubkey,
+    pub start_slot: u64,
+    pub end_slot: u64,
+    pub fee_share_bps: u16,
+    pub prize_pool: u64,
+    pub leaders: [SeasonLeader; SEASON_LEADERBOARD_SIZE],
+}
+
+#[account]
+#[derive(Debug, Default)]
+pub struct SeasonEntry {
+    pub season: Pubkey,
+    pub player: Pubkey,
+    pub points: u64,
+}
+
+/// Caps spending at `limit` per epoch. A new limit waits in `pending_limit` until the next epoch.
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Eq, PartialEq)]
+pub struct EpochBudget {
+    pub limit: u64,
+    pub pending_limit: Option<u64>,
+    pub epoch: u64,
+    pub spent: u64,
+}
+
+/// Collects protocol fees for one mint. The admin spends it within `budget`.
+#[account]
+#[derive(Debug, Default)]
+pub struct Treasury {
+    pub mint: Pubkey,
+    pub bump: u8,
+    pub budget: EpochBudget,
+    pub total_collected: u64,
+}
+
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Eq, PartialEq)]
+pub struct FeeRecipient {
+    pub owner: Pubkey,
+    pub bps: u16,
+}
+
+/// How `distribute_treasury` splits a mint's treasury. The first recipient also takes the
+/// rounding remainder. Distributions are capped by their own `budget`, separate from the
+/// admin's.
+#[account]
+#[derive(Debug, Default)]
+pub struct FeeSplit {
+    pub mint: Pubkey,
+    pub bump: u8,
+    pub recipient_count: u8,
+    pub recipients: [FeeRecipient; MAX_FEE_RECIPIENTS],
+    pub budget: EpochBudget,
+}
+
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, Eq, PartialEq)]
+pub enum ExitCondition {
+    UsersAtMost(u32),
+    CollateralAtLeast(u64),
+    SlotAtLeast(u64),
+}
+
+impl Default for ExitCondition {
+    fn default() -> Self {
+        ExitCondition::SlotAtLeast(u64::MAX)
+    }
+}
+
+#[account]
+#[derive(Debug, Default)]
+pub struct ExitOrder {
+    pub owner: Pubkey,
+    pub user_position: Pubkey,
+    pub condition: ExitCondition,
+    pub tip: u64,
 }
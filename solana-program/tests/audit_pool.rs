@@ -0,0 +1,63 @@
+mod common;
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chicken::{
+    actions::{bps, bps_up, InitializePoolArgs},
+    events::PoolAudited,
+    state::{Pool, PoolMode},
+};
+use common::*;
+
+#[test_log::test]
+fn test_audit_pool_tracks_principal() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, _) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, _) = setup_user(&mut ctx, deposit_amount)?;
+    for user in [&user1, &user2] {
+        deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, user, deposit_amount)?;
+    }
+    let logs = audit_pool(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1)?;
+    let audit = decode_event::<PoolAudited>(&logs).unwrap();
+    assert!(audit.balanced);
+
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    let fee = bps_up(deposit_amount, pool.deposit_fee_bps)?;
+    let collateral = bps(deposit_amount - fee, pool.collateral_bps)?;
+    assert_eq!(
+        pool.total_principal,
+        2 * (deposit_amount - fee - collateral)
+    );
+
+    // a donation to the vault is not principal and must not change anyone's payout
+    let quote = quote_withdraw(&mut ctx.svm, &ctx.pool_key, &user1)?;
+    mint_to(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.mint_authority,
+        &ctx.pool_ata,
+        5_000,
+    )?;
+    assert_eq!(quote_withdraw(&mut ctx.svm, &ctx.pool_key, &user1)?, quote);
+    let logs = audit_pool(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user2)?;
+    let audit = decode_event::<PoolAudited>(&logs).unwrap();
+    assert!(!audit.balanced);
+    assert_eq!(audit.vault_amount, balance(&ctx.svm, &ctx.pool_ata));
+    let after = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let after = Pool::deserialize(&mut &after.data[8..]).unwrap();
+    assert_eq!(after.total_principal, pool.total_principal);
+    Ok(())
+}
@@ -1,17 +1,30 @@
-use anchor_lang::{prelude::AccountMeta, AnchorSerialize, Discriminator};
-use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_lang::{
+    __private::base64::{engine::general_purpose::STANDARD, Engine},
+    prelude::AccountMeta,
+    AnchorDeserialize, AnchorSerialize, Discriminator,
+};
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id, token_2022::spl_token_2022,
+};
 use anyhow::Result;
 use chicken::{
-    actions::InitializePoolArgs,
-    instruction::{Deposit, InitializePool, Withdraw},
+    actions::{InitializePoolArgs, InitializeSeasonArgs, Quote},
+    instruction::{
+        AuditPool, CheckIn, ClaimSeasonPrize, Deposit, DepositShares, DistributeTreasury,
+        Eliminate, ExecuteExitOrder, InitializeAdmin, InitializePool, InitializeSeason,
+        InitializeTreasury, PlaceExitOrder, PokePool, QuoteDeposit, QuoteWithdraw, RemovePool,
+        RevealEnd, SetFeeSplit, SetPositionDelegate, SetTreasuryLimit, Settle, SweepDust,
+        SweepFeesToTreasury, TransferPosition, Withdraw, WithdrawFromTreasury, WithdrawShares,
+    },
+    state::{ExitCondition, FeeRecipient, Pool, PositionDelegate, UserPosition},
     ID,
 };
 use litesvm::LiteSVM;
 use litesvm_token::{spl_token, CreateAssociatedTokenAccount, CreateMint, MintTo};
-use solana_program::pubkey::Pubkey;
+use solana_program::{hash::Hash, pubkey::Pubkey, slot_hashes::SlotHashes};
 use solana_sdk::{
-    instruction::Instruction, signature::Keypair, signer::Signer, system_program,
-    transaction::Transaction,
+    instruction::Instruction, program_pack::Pack, signature::Keypair, signer::Signer,
+    system_program, transaction::Transaction,
 };
 
 pub fn load_program(svm: &mut LiteSVM) -> anyhow::Result<()> {
@@ -66,6 +79,13 @@ pub fn setup_ata(
         .map_err(|_| anyhow::anyhow!("Failed to create associated token account"))
 }
 
+pub fn balance(svm: &LiteSVM, ata: &Pubkey) -> u64 {
+    let account = svm.get_account(ata).unwrap();
+    spl_token::state::Account::unpack(&account.data)
+        .unwrap()
+        .amount
+}
+
 pub fn init_pool(
     svm: &mut LiteSVM,
     creator: &Keypair,
@@ -78,6 +98,11 @@ pub fn init_pool(
     };
     let data = data.try_to_vec()?;
     let pool_ata = get_associated_token_address_with_program_id(pool, mint, &spl_token::ID);
+    let share_mint = if pool_init_args.share_tokens {
+        share_mint_key(pool)
+    } else {
+        chicken::ID
+    };
     let ix = Instruction::new_with_bytes(
         chicken::ID,
         &[InitializePool::DISCRIMINATOR.as_ref(), data.as_slice()].concat(),
@@ -88,6 +113,7 @@ pub fn init_pool(
             AccountMeta::new(pool_ata, false),
             AccountMeta::new_readonly(spl_token::ID, false),
             AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new(share_mint, false),
         ],
     );
     let tx = Transaction::new_signed_with_payer(
@@ -108,6 +134,24 @@ pub fn deposit(
     user: &Keypair,
     amount: u64,
 ) -> Result<Pubkey, anyhow::Error> {
+    deposit_with_limits(svm, mint, pool, user, amount, 10_000, 10_000)
+}
+
+pub fn deposit_with_limits(
+    svm: &mut LiteSVM,
+    mint: &Pubkey,
+    pool: &Pubkey,
+    user: &Keypair,
+    amount: u64,
+    max_fee_bps: u16,
+    max_collateral_bps: u16,
+) -> Result<Pubkey, anyhow::Error> {
+    let data = Deposit {
+        amount,
+        max_fee_bps,
+        max_collateral_bps,
+    }
+    .try_to_vec()?;
     let user_position = Pubkey::find_program_address(
         &[
             b"user_position".as_ref(),
@@ -122,11 +166,7 @@ pub fn deposit(
         get_associated_token_address_with_program_id(&user.pubkey(), mint, &spl_token::ID);
     let ix = Instruction::new_with_bytes(
         chicken::ID,
-        &[
-            Deposit::DISCRIMINATOR.as_ref(),
-            amount.to_le_bytes().as_slice(),
-        ]
-        .concat(),
+        &[Deposit::DISCRIMINATOR.as_ref(), data.as_slice()].concat(),
         vec![
             AccountMeta::new(*pool, false),
             AccountMeta::new(user.pubkey(), true),
@@ -134,9 +174,14 @@ pub fn deposit(
             AccountMeta::new(pool_ata, false),
             AccountMeta::new(user_ata, false),
             AccountMeta::new(user_position, false),
+            AccountMeta::new(player_profile_key(&user.pubkey()), false),
             AccountMeta::new(*mint, false),
             AccountMeta::new_readonly(spl_token::ID, false),
             AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
         ],
     );
     let tx = Transaction::new_signed_with_payer(
@@ -147,6 +192,7 @@ pub fn deposit(
     );
     svm.send_transaction(tx)
         .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    assert_pool_balanced(svm, pool);
     Ok(user_position)
 }
 
@@ -156,6 +202,17 @@ pub fn withdraw(
     mint: &Pubkey,
     user: &Keypair,
 ) -> Result<(), anyhow::Error> {
+    withdraw_with_min_out(svm, pool, mint, user, 0)
+}
+
+pub fn withdraw_with_min_out(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    mint: &Pubkey,
+    user: &Keypair,
+    min_out: u64,
+) -> Result<(), anyhow::Error> {
+    let data = Withdraw { min_out }.try_to_vec()?;
     let user_position = Pubkey::find_program_address(
         &[
             b"user_position".as_ref(),
@@ -170,7 +227,7 @@ pub fn withdraw(
         get_associated_token_address_with_program_id(&user.pubkey(), mint, &spl_token::ID);
     let ix = Instruction::new_with_bytes(
         chicken::ID,
-        Withdraw::DISCRIMINATOR.as_ref(),
+        &[Withdraw::DISCRIMINATOR.as_ref(), data.as_slice()].concat(),
         vec![
             AccountMeta::new(*pool, false),
             AccountMeta::new(user.pubkey(), true),
@@ -178,9 +235,17 @@ pub fn withdraw(
             AccountMeta::new(pool_ata, false),
             AccountMeta::new(user_ata, false),
             AccountMeta::new(user_position, false),
+            AccountMeta::new(player_profile_key(&user.pubkey()), false),
             AccountMeta::new(*mint, false),
             AccountMeta::new_readonly(spl_token::ID, false),
             AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
         ],
     );
     let tx = Transaction::new_signed_with_payer(
@@ -192,6 +257,1234 @@ pub fn withdraw(
     let res = svm
         .send_transaction(tx)
         .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    assert_pool_balanced(svm, pool);
+    Ok(())
+}
+
+fn simulate_quote(svm: &mut LiteSVM, ix: Instruction, payer: &Keypair) -> Result<Quote> {
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        svm.latest_blockhash(),
+    );
+    let res = svm
+        .simulate_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to simulate transaction: {:?}", e))?;
+    Ok(Quote::try_from_slice(&res.meta.return_data.data)?)
+}
+
+pub fn quote_deposit(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    payer: &Keypair,
+    amount: u64,
+) -> Result<Quote> {
+    let data = QuoteDeposit { amount }.try_to_vec()?;
+    let pool_account = svm.get_account(pool).unwrap();
+    let pool_state = Pool::deserialize(&mut &pool_account.data[8..])?;
+    let pool_ata = get_associated_token_address_with_program_id(
+        pool,
+        &pool_state.collateral_mint,
+        &spl_token::ID,
+    );
+    let user_position = user_position_key(pool, &payer.pubkey());
+    let user_position = if svm
+        .get_account(&user_position)
+        .is_some_and(|account| !account.data.is_empty())
+    {
+        user_position
+    } else {
+        chicken::ID
+    };
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        &[QuoteDeposit::DISCRIMINATOR.as_ref(), data.as_slice()].concat(),
+        vec![
+            AccountMeta::new_readonly(*pool, false),
+            AccountMeta::new_readonly(pool_ata, false),
+            AccountMeta::new_readonly(user_position, false),
+        ],
+    );
+    simulate_quote(svm, ix, payer)
+}
+
+pub fn quote_withdraw(svm: &mut LiteSVM, pool: &Pubkey, owner: &Keypair) -> Result<Quote> {
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        QuoteWithdraw::DISCRIMINATOR.as_ref(),
+        vec![
+            AccountMeta::new_readonly(*pool, false),
+            AccountMeta::new_readonly(user_position_key(pool, &owner.pubkey()), false),
+        ],
+    );
+    simulate_quote(svm, ix, owner)
+}
+
+pub fn set_position_delegate(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    owner: &Keypair,
+    delegate: Option<PositionDelegate>,
+) -> Result<(), anyhow::Error> {
+    let data = SetPositionDelegate { delegate }.try_to_vec()?;
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        &[SetPositionDelegate::DISCRIMINATOR.as_ref(), data.as_slice()].concat(),
+        vec![
+            AccountMeta::new_readonly(*pool, false),
+            AccountMeta::new_readonly(owner.pubkey(), true),
+            AccountMeta::new(user_position_key(pool, &owner.pubkey()), false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    Ok(())
+}
+
+pub fn withdraw_as_delegate(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    mint: &Pubkey,
+    delegate: &Keypair,
+    owner: &Pubkey,
+    recipient: &Pubkey,
+) -> Result<(), anyhow::Error> {
+    let data = Withdraw { min_out: 0 }.try_to_vec()?;
+    let user_position = user_position_key(pool, owner);
+    let rent_receiver = svm.get_account(&user_position).map_or(*owner, |account| {
+        UserPosition::deserialize(&mut &account.data[8..])
+            .unwrap()
+            .payer
+    });
+    let pool_ata = get_associated_token_address_with_program_id(pool, mint, &spl_token::ID);
+    let recipient_ata =
+        get_associated_token_address_with_program_id(recipient, mint, &spl_token::ID);
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        &[Withdraw::DISCRIMINATOR.as_ref(), data.as_slice()].concat(),
+        vec![
+            AccountMeta::new(*pool, false),
+            AccountMeta::new(delegate.pubkey(), true),
+            AccountMeta::new(delegate.pubkey(), true),
+            AccountMeta::new(pool_ata, false),
+            AccountMeta::new(recipient_ata, false),
+            AccountMeta::new(user_position, false),
+            AccountMeta::new(player_profile_key(owner), false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new(rent_receiver, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&delegate.pubkey()),
+        &[&delegate],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    assert_pool_balanced(svm, pool);
+    Ok(())
+}
+
+pub fn exit_order_key(user_position: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"exit_order".as_ref(), user_position.as_ref()],
+        &chicken::ID,
+    )
+    .0
+}
+
+pub fn place_exit_order(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    owner: &Keypair,
+    condition: ExitCondition,
+    tip: u64,
+) -> Result<Pubkey, anyhow::Error> {
+    let user_position = user_position_key(pool, &owner.pubkey());
+    let exit_order = exit_order_key(&user_position);
+    let data = PlaceExitOrder { condition, tip }.try_to_vec()?;
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        &[PlaceExitOrder::DISCRIMINATOR.as_ref(), data.as_slice()].concat(),
+        vec![
+            AccountMeta::new_readonly(*pool, false),
+            AccountMeta::new(owner.pubkey(), true),
+            AccountMeta::new_readonly(user_position, false),
+            AccountMeta::new(exit_order, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    Ok(exit_order)
+}
+
+pub fn execute_exit_order(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    mint: &Pubkey,
+    keeper: &Keypair,
+    owner: &Pubkey,
+) -> Result<(), anyhow::Error> {
+    let user_position = user_position_key(pool, owner);
+    let pool_ata = get_associated_token_address_with_program_id(pool, mint, &spl_token::ID);
+    let owner_ata = get_associated_token_address_with_program_id(owner, mint, &spl_token::ID);
+    let rent_receiver = svm.get_account(&user_position).map_or(*owner, |account| {
+        UserPosition::deserialize(&mut &account.data[8..])
+            .unwrap()
+            .payer
+    });
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        ExecuteExitOrder::DISCRIMINATOR.as_ref(),
+        vec![
+            AccountMeta::new(*pool, false),
+            AccountMeta::new(keeper.pubkey(), true),
+            AccountMeta::new(*owner, false),
+            AccountMeta::new(pool_ata, false),
+            AccountMeta::new(owner_ata, false),
+            AccountMeta::new(user_position, false),
+            AccountMeta::new(exit_order_key(&user_position), false),
+            AccountMeta::new(player_profile_key(owner), false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new(rent_receiver, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&keeper.pubkey()),
+        &[&keeper],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    assert_pool_balanced(svm, pool);
+    Ok(())
+}
+
+pub fn transfer_position(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    owner: &Keypair,
+    new_owner: &Pubkey,
+) -> Result<(), anyhow::Error> {
+    transfer_position_with_payer(svm, pool, owner, owner, new_owner)
+}
+
+pub fn transfer_position_with_payer(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    owner: &Keypair,
+    payer: &Keypair,
+    new_owner: &Pubkey,
+) -> Result<(), anyhow::Error> {
+    let data = TransferPosition {
+        new_owner: *new_owner,
+    }
+    .try_to_vec()?;
+    let user_position = user_position_key(pool, &owner.pubkey());
+    let rent_receiver = svm
+        .get_account(&user_position)
+        .map_or(payer.pubkey(), |account| {
+            UserPosition::deserialize(&mut &account.data[8..])
+                .unwrap()
+                .payer
+        });
+    let exit_order = exit_order_key(&user_position);
+    let exit_order = if svm
+        .get_account(&exit_order)
+        .is_some_and(|account| !account.data.is_empty())
+    {
+        exit_order
+    } else {
+        chicken::ID
+    };
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        &[TransferPosition::DISCRIMINATOR.as_ref(), data.as_slice()].concat(),
+        vec![
+            AccountMeta::new(*pool, false),
+            AccountMeta::new(owner.pubkey(), true),
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(user_position, false),
+            AccountMeta::new(user_position_key(pool, new_owner), false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new(rent_receiver, false),
+            AccountMeta::new(exit_order, false),
+            AccountMeta::new(player_profile_key(new_owner), false),
+        ],
+    );
+    let signers = if owner.pubkey() == payer.pubkey() {
+        vec![owner]
+    } else {
+        vec![owner, payer]
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &signers,
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    assert_pool_balanced(svm, pool);
+    Ok(())
+}
+
+pub fn share_mint_key(pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"share_mint".as_ref(), pool.as_ref()], &chicken::ID).0
+}
+
+pub fn deposit_shares(
+    svm: &mut LiteSVM,
+    mint: &Pubkey,
+    pool: &Pubkey,
+    user: &Keypair,
+    amount: u64,
+) -> Result<Pubkey, anyhow::Error> {
+    deposit_shares_with_limits(svm, mint, pool, user, amount, 10_000, 10_000)
+}
+
+pub fn deposit_shares_with_limits(
+    svm: &mut LiteSVM,
+    mint: &Pubkey,
+    pool: &Pubkey,
+    user: &Keypair,
+    amount: u64,
+    max_fee_bps: u16,
+    max_collateral_bps: u16,
+) -> Result<Pubkey, anyhow::Error> {
+    let data = DepositShares {
+        amount,
+        max_fee_bps,
+        max_collateral_bps,
+    }
+    .try_to_vec()?;
+    let share_mint = share_mint_key(pool);
+    let pool_ata = get_associated_token_address_with_program_id(pool, mint, &spl_token::ID);
+    let user_ata =
+        get_associated_token_address_with_program_id(&user.pubkey(), mint, &spl_token::ID);
+    let user_share_ata =
+        get_associated_token_address_with_program_id(&user.pubkey(), &share_mint, &spl_token::ID);
+    if svm.get_account(&user_share_ata).is_none() {
+        CreateAssociatedTokenAccount::new(svm, user, &share_mint)
+            .owner(&user.pubkey())
+            .send()
+            .map_err(|_| anyhow::anyhow!("Failed to create associated token account"))?;
+    }
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        &[DepositShares::DISCRIMINATOR.as_ref(), data.as_slice()].concat(),
+        vec![
+            AccountMeta::new(*pool, false),
+            AccountMeta::new_readonly(user.pubkey(), true),
+            AccountMeta::new(pool_ata, false),
+            AccountMeta::new(user_ata, false),
+            AccountMeta::new(share_mint, false),
+            AccountMeta::new(user_share_ata, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&user.pubkey()),
+        &[&user],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    assert_pool_balanced(svm, pool);
+    Ok(user_share_ata)
+}
+
+pub fn withdraw_shares(
+    svm: &mut LiteSVM,
+    mint: &Pubkey,
+    pool: &Pubkey,
+    user: &Keypair,
+    shares: u64,
+) -> Result<(), anyhow::Error> {
+    let share_mint = share_mint_key(pool);
+    let pool_ata = get_associated_token_address_with_program_id(pool, mint, &spl_token::ID);
+    let user_ata =
+        get_associated_token_address_with_program_id(&user.pubkey(), mint, &spl_token::ID);
+    let user_share_ata =
+        get_associated_token_address_with_program_id(&user.pubkey(), &share_mint, &spl_token::ID);
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        &[
+            WithdrawShares::DISCRIMINATOR.as_ref(),
+            shares.to_le_bytes().as_slice(),
+        ]
+        .concat(),
+        vec![
+            AccountMeta::new(*pool, false),
+            AccountMeta::new_readonly(user.pubkey(), true),
+            AccountMeta::new(pool_ata, false),
+            AccountMeta::new(user_ata, false),
+            AccountMeta::new(share_mint, false),
+            AccountMeta::new(user_share_ata, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&user.pubkey()),
+        &[&user],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    assert_pool_balanced(svm, pool);
+    Ok(())
+}
+
+pub fn remove_pool(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    creator: &Keypair,
+    share_mint: Option<Pubkey>,
+) -> Result<(), anyhow::Error> {
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        RemovePool::DISCRIMINATOR.as_ref(),
+        vec![
+            AccountMeta::new(*pool, false),
+            AccountMeta::new(creator.pubkey(), true),
+            AccountMeta::new(creator.pubkey(), true),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(share_mint.unwrap_or(chicken::ID), false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&creator.pubkey()),
+        &[&creator],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    Ok(())
+}
+
+pub fn receipt_mint_key(user_position: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"receipt".as_ref(), user_position.as_ref()], &chicken::ID).0
+}
+
+pub fn deposit_with_receipt(
+    svm: &mut LiteSVM,
+    mint: &Pubkey,
+    pool: &Pubkey,
+    user: &Keypair,
+    amount: u64,
+) -> Result<(Pubkey, Pubkey), anyhow::Error> {
+    let data = Deposit {
+        amount,
+        max_fee_bps: 10_000,
+        max_collateral_bps: 10_000,
+    }
+    .try_to_vec()?;
+    let user_position = user_position_key(pool, &user.pubkey());
+    let receipt_mint = receipt_mint_key(&user_position);
+    let pool_ata = get_associated_token_address_with_program_id(pool, mint, &spl_token::ID);
+    let user_ata =
+        get_associated_token_address_with_program_id(&user.pubkey(), mint, &spl_token::ID);
+    let user_receipt_ata = get_associated_token_address_with_program_id(
+        &user.pubkey(),
+        &receipt_mint,
+        &spl_token_2022::ID,
+    );
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        &[Deposit::DISCRIMINATOR.as_ref(), data.as_slice()].concat(),
+        vec![
+            AccountMeta::new(*pool, false),
+            AccountMeta::new(user.pubkey(), true),
+            AccountMeta::new(user.pubkey(), true),
+            AccountMeta::new(pool_ata, false),
+            AccountMeta::new(user_ata, false),
+            AccountMeta::new(user_position, false),
+            AccountMeta::new(player_profile_key(&user.pubkey()), false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new(receipt_mint, false),
+            AccountMeta::new(user_receipt_ata, false),
+            AccountMeta::new_readonly(spl_token_2022::ID, false),
+            AccountMeta::new_readonly(anchor_spl::associated_token::ID, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&user.pubkey()),
+        &[&user],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    assert_pool_balanced(svm, pool);
+    Ok((user_position, receipt_mint))
+}
+
+pub fn withdraw_with_receipt(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    mint: &Pubkey,
+    holder: &Keypair,
+    owner: &Pubkey,
+    user_position: &Pubkey,
+    receipt_mint: &Pubkey,
+) -> Result<(), anyhow::Error> {
+    let data = Withdraw { min_out: 0 }.try_to_vec()?;
+    let pool_ata = get_associated_token_address_with_program_id(pool, mint, &spl_token::ID);
+    let holder_ata =
+        get_associated_token_address_with_program_id(&holder.pubkey(), mint, &spl_token::ID);
+    let holder_receipt_ata = get_associated_token_address_with_program_id(
+        &holder.pubkey(),
+        receipt_mint,
+        &spl_token_2022::ID,
+    );
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        &[Withdraw::DISCRIMINATOR.as_ref(), data.as_slice()].concat(),
+        vec![
+            AccountMeta::new(*pool, false),
+            AccountMeta::new(holder.pubkey(), true),
+            AccountMeta::new(holder.pubkey(), true),
+            AccountMeta::new(pool_ata, false),
+            AccountMeta::new(holder_ata, false),
+            AccountMeta::new(*user_position, false),
+            AccountMeta::new(player_profile_key(owner), false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new(*receipt_mint, false),
+            AccountMeta::new(holder_receipt_ata, false),
+            AccountMeta::new_readonly(spl_token_2022::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&holder.pubkey()),
+        &[&holder],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    assert_pool_balanced(svm, pool);
+    Ok(())
+}
+
+/// Settles a single receipt-backed position, paying `holder`.
+pub fn settle_receipt_position(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    mint: &Pubkey,
+    cranker: &Keypair,
+    owner: &Pubkey,
+    holder: &Pubkey,
+) -> Result<(), anyhow::Error> {
+    let pool_ata = get_associated_token_address_with_program_id(pool, mint, &spl_token::ID);
+    let user_position = user_position_key(pool, owner);
+    let holder_ata = get_associated_token_address_with_program_id(holder, mint, &spl_token::ID);
+    let receipt_mint = receipt_mint_key(&user_position);
+    let holder_receipt_ata =
+        get_associated_token_address_with_program_id(holder, &receipt_mint, &spl_token_2022::ID);
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        Settle::DISCRIMINATOR.as_ref(),
+        vec![
+            AccountMeta::new(*pool, false),
+            AccountMeta::new_readonly(cranker.pubkey(), true),
+            AccountMeta::new(pool_ata, false),
+            AccountMeta::new(chicken::ID, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(spl_token_2022::ID, false),
+            AccountMeta::new(user_position, false),
+            AccountMeta::new(holder_ata, false),
+            AccountMeta::new(*owner, false),
+            AccountMeta::new(player_profile_key(owner), false),
+            AccountMeta::new(holder_receipt_ata, false),
+            AccountMeta::new(receipt_mint, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&cranker.pubkey()),
+        &[&cranker],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    assert_pool_balanced(svm, pool);
+    Ok(())
+}
+
+pub fn initialize_admin(svm: &mut LiteSVM, admin: &Keypair) -> Result<Pubkey, anyhow::Error> {
+    let admin_record = Pubkey::find_program_address(&[b"admin".as_ref()], &chicken::ID).0;
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        InitializeAdmin::DISCRIMINATOR.as_ref(),
+        vec![
+            AccountMeta::new(admin_record, false),
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&admin.pubkey()),
+        &[&admin],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    Ok(admin_record)
+}
+
+pub fn season_key(season_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"season".as_ref(), season_id.to_le_bytes().as_ref()],
+        &chicken::ID,
+    )
+    .0
+}
+
+pub fn season_entry_key(season: &Pubkey, player: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"season_entry".as_ref(), season.as_ref(), player.as_ref()],
+        &chicken::ID,
+    )
+    .0
+}
+
+pub fn initialize_season(
+    svm: &mut LiteSVM,
+    admin: &Keypair,
+    mint: &Pubkey,
+    args: InitializeSeasonArgs,
+) -> Result<Pubkey, anyhow::Error> {
+    let season = season_key(args.season_id);
+    let season_ata = get_associated_token_address_with_program_id(&season, mint, &spl_token::ID);
+    let admin_record = Pubkey::find_program_address(&[b"admin".as_ref()], &chicken::ID).0;
+    let data = InitializeSeason { args }.try_to_vec()?;
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        &[InitializeSeason::DISCRIMINATOR.as_ref(), data.as_slice()].concat(),
+        vec![
+            AccountMeta::new(season, false),
+            AccountMeta::new(season_ata, false),
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(admin_record, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(anchor_spl::associated_token::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&admin.pubkey()),
+        &[&admin],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    Ok(season)
+}
+
+pub fn withdraw_in_season(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    mint: &Pubkey,
+    user: &Keypair,
+    season: &Pubkey,
+) -> Result<(), anyhow::Error> {
+    let data = Withdraw { min_out: 0 }.try_to_vec()?;
+    let pool_ata = get_associated_token_address_with_program_id(pool, mint, &spl_token::ID);
+    let user_ata =
+        get_associated_token_address_with_program_id(&user.pubkey(), mint, &spl_token::ID);
+    let season_ata = get_associated_token_address_with_program_id(season, mint, &spl_token::ID);
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        &[Withdraw::DISCRIMINATOR.as_ref(), data.as_slice()].concat(),
+        vec![
+            AccountMeta::new(*pool, false),
+            AccountMeta::new(user.pubkey(), true),
+            AccountMeta::new(user.pubkey(), true),
+            AccountMeta::new(pool_ata, false),
+            AccountMeta::new(user_ata, false),
+            AccountMeta::new(user_position_key(pool, &user.pubkey()), false),
+            AccountMeta::new(player_profile_key(&user.pubkey()), false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+            AccountMeta::new(*season, false),
+            AccountMeta::new(season_entry_key(season, &user.pubkey()), false),
+            AccountMeta::new(season_ata, false),
+            AccountMeta::new_readonly(chicken::ID, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&user.pubkey()),
+        &[&user],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    assert_pool_balanced(svm, pool);
+    Ok(())
+}
+
+pub fn claim_season_prize(
+    svm: &mut LiteSVM,
+    season: &Pubkey,
+    mint: &Pubkey,
+    player: &Keypair,
+) -> Result<(), anyhow::Error> {
+    let season_ata = get_associated_token_address_with_program_id(season, mint, &spl_token::ID);
+    let player_ata =
+        get_associated_token_address_with_program_id(&player.pubkey(), mint, &spl_token::ID);
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        ClaimSeasonPrize::DISCRIMINATOR.as_ref(),
+        vec![
+            AccountMeta::new(*season, false),
+            AccountMeta::new(season_ata, false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+            AccountMeta::new(player_ata, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&player.pubkey()),
+        &[&player],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    Ok(())
+}
+
+/// Leaves `slot` as the only block the SlotHashes sysvar knows about.
+pub fn set_slot_hash(svm: &mut LiteSVM, slot: u64, hash: [u8; 32]) {
+    svm.set_sysvar(&SlotHashes::new(&[(slot, Hash::new_from_array(hash))]));
+}
+
+pub fn reveal_end(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    revealer: &Keypair,
+    secret: [u8; 32],
+    salt: [u8; 32],
+) -> Result<(), anyhow::Error> {
+    let data = RevealEnd { secret, salt }.try_to_vec()?;
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        &[RevealEnd::DISCRIMINATOR.as_ref(), data.as_slice()].concat(),
+        vec![
+            AccountMeta::new(*pool, false),
+            AccountMeta::new(revealer.pubkey(), true),
+            AccountMeta::new_readonly(solana_program::sysvar::slot_hashes::ID, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&revealer.pubkey()),
+        &[&revealer],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    assert_pool_balanced(svm, pool);
+    Ok(())
+}
+
+pub fn check_in(svm: &mut LiteSVM, pool: &Pubkey, owner: &Keypair) -> Result<(), anyhow::Error> {
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        CheckIn::DISCRIMINATOR.as_ref(),
+        vec![
+            AccountMeta::new(*pool, false),
+            AccountMeta::new_readonly(owner.pubkey(), true),
+            AccountMeta::new(user_position_key(pool, &owner.pubkey()), false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    assert_pool_balanced(svm, pool);
+    Ok(())
+}
+
+pub fn eliminate(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    cranker: &Keypair,
+    owner: &Pubkey,
+) -> Result<(), anyhow::Error> {
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        Eliminate::DISCRIMINATOR.as_ref(),
+        vec![
+            AccountMeta::new(*pool, false),
+            AccountMeta::new_readonly(cranker.pubkey(), true),
+            AccountMeta::new(user_position_key(pool, owner), false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&cranker.pubkey()),
+        &[&cranker],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    assert_pool_balanced(svm, pool);
+    Ok(())
+}
+
+/// The vault must always hold exactly the principal, collateral and fees the pool has booked.
+pub fn assert_pool_balanced(svm: &LiteSVM, pool: &Pubkey) {
+    let account = svm.get_account(pool).unwrap();
+    let pool_state = Pool::deserialize(&mut &account.data[8..]).unwrap();
+    let token_program = svm.get_account(&pool_state.collateral_mint).unwrap().owner;
+    let pool_ata = get_associated_token_address_with_program_id(
+        pool,
+        &pool_state.collateral_mint,
+        &token_program,
+    );
+    let vault = svm.get_account(&pool_ata).unwrap();
+    let vault_amount =
+        spl_token::state::Account::unpack(&vault.data[..spl_token::state::Account::LEN])
+            .unwrap()
+            .amount;
+    assert_eq!(
+        vault_amount,
+        pool_state.total_principal + pool_state.collateral_amount + pool_state.fee_amount,
+        "vault out of balance: {pool_state:?}"
+    );
+}
+
+/// Finds the first `T` event in `logs`. Anchor logs events as base64 behind "Program data: ",
+/// prefixed with their 8-byte discriminator.
+pub fn decode_event<T: AnchorDeserialize + Discriminator>(logs: &[String]) -> Option<T> {
+    logs.iter().find_map(|log| {
+        let data = STANDARD.decode(log.strip_prefix("Program data: ")?).ok()?;
+        if data.get(..8)? != T::DISCRIMINATOR.as_ref() {
+            return None;
+        }
+        T::deserialize(&mut &data[8..]).ok()
+    })
+}
+
+pub fn audit_pool(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    mint: &Pubkey,
+    payer: &Keypair,
+) -> Result<Vec<String>, anyhow::Error> {
+    let pool_ata = get_associated_token_address_with_program_id(pool, mint, &spl_token::ID);
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        AuditPool::DISCRIMINATOR.as_ref(),
+        vec![
+            AccountMeta::new_readonly(*pool, false),
+            AccountMeta::new_readonly(pool_ata, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        svm.latest_blockhash(),
+    );
+    let res = svm
+        .send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    Ok(res.logs)
+}
+
+pub fn treasury_key(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"treasury".as_ref(), mint.as_ref()], &chicken::ID).0
+}
+
+pub fn initialize_treasury(
+    svm: &mut LiteSVM,
+    admin: &Keypair,
+    mint: &Pubkey,
+    epoch_limit: u64,
+) -> Result<Pubkey, anyhow::Error> {
+    let treasury = treasury_key(mint);
+    let treasury_ata =
+        get_associated_token_address_with_program_id(&treasury, mint, &spl_token::ID);
+    let admin_record = Pubkey::find_program_address(&[b"admin".as_ref()], &chicken::ID).0;
+    let data = InitializeTreasury { epoch_limit }.try_to_vec()?;
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        &[InitializeTreasury::DISCRIMINATOR.as_ref(), data.as_slice()].concat(),
+        vec![
+            AccountMeta::new(treasury, false),
+            AccountMeta::new(treasury_ata, false),
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(admin_record, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(anchor_spl::associated_token::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&admin.pubkey()),
+        &[&admin],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    Ok(treasury_ata)
+}
+
+pub fn set_treasury_limit(
+    svm: &mut LiteSVM,
+    admin: &Keypair,
+    mint: &Pubkey,
+    epoch_limit: u64,
+) -> Result<(), anyhow::Error> {
+    let admin_record = Pubkey::find_program_address(&[b"admin".as_ref()], &chicken::ID).0;
+    let data = SetTreasuryLimit { epoch_limit }.try_to_vec()?;
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        &[SetTreasuryLimit::DISCRIMINATOR.as_ref(), data.as_slice()].concat(),
+        vec![
+            AccountMeta::new(treasury_key(mint), false),
+            AccountMeta::new_readonly(admin.pubkey(), true),
+            AccountMeta::new_readonly(admin_record, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&admin.pubkey()),
+        &[&admin],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    Ok(())
+}
+
+pub fn sweep_fees_to_treasury(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    mint: &Pubkey,
+    payer: &Keypair,
+) -> Result<(), anyhow::Error> {
+    let pool_ata = get_associated_token_address_with_program_id(pool, mint, &spl_token::ID);
+    let treasury = treasury_key(mint);
+    let treasury_ata =
+        get_associated_token_address_with_program_id(&treasury, mint, &spl_token::ID);
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        SweepFeesToTreasury::DISCRIMINATOR.as_ref(),
+        vec![
+            AccountMeta::new(*pool, false),
+            AccountMeta::new(pool_ata, false),
+            AccountMeta::new(treasury, false),
+            AccountMeta::new(treasury_ata, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    assert_pool_balanced(svm, pool);
+    Ok(())
+}
+
+pub fn withdraw_from_treasury(
+    svm: &mut LiteSVM,
+    admin: &Keypair,
+    mint: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+) -> Result<(), anyhow::Error> {
+    let treasury = treasury_key(mint);
+    let treasury_ata =
+        get_associated_token_address_with_program_id(&treasury, mint, &spl_token::ID);
+    let admin_record = Pubkey::find_program_address(&[b"admin".as_ref()], &chicken::ID).0;
+    let data = WithdrawFromTreasury { amount }.try_to_vec()?;
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        &[
+            WithdrawFromTreasury::DISCRIMINATOR.as_ref(),
+            data.as_slice(),
+        ]
+        .concat(),
+        vec![
+            AccountMeta::new(treasury, false),
+            AccountMeta::new(treasury_ata, false),
+            AccountMeta::new(*recipient, false),
+            AccountMeta::new_readonly(admin.pubkey(), true),
+            AccountMeta::new_readonly(admin_record, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&admin.pubkey()),
+        &[&admin],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    Ok(())
+}
+
+pub fn fee_split_key(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"fee_split".as_ref(), mint.as_ref()], &chicken::ID).0
+}
+
+pub fn set_fee_split(
+    svm: &mut LiteSVM,
+    admin: &Keypair,
+    mint: &Pubkey,
+    recipients: Vec<FeeRecipient>,
+    epoch_limit: u64,
+) -> Result<(), anyhow::Error> {
+    let admin_record = Pubkey::find_program_address(&[b"admin".as_ref()], &chicken::ID).0;
+    let data = SetFeeSplit {
+        recipients,
+        epoch_limit,
+    }
+    .try_to_vec()?;
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        &[SetFeeSplit::DISCRIMINATOR.as_ref(), data.as_slice()].concat(),
+        vec![
+            AccountMeta::new(fee_split_key(mint), false),
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(admin_record, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&admin.pubkey()),
+        &[&admin],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    Ok(())
+}
+
+pub fn distribute_treasury(
+    svm: &mut LiteSVM,
+    mint: &Pubkey,
+    payer: &Keypair,
+    recipient_accounts: &[Pubkey],
+) -> Result<(), anyhow::Error> {
+    let treasury = treasury_key(mint);
+    let treasury_ata =
+        get_associated_token_address_with_program_id(&treasury, mint, &spl_token::ID);
+    let mut accounts = vec![
+        AccountMeta::new_readonly(treasury, false),
+        AccountMeta::new(treasury_ata, false),
+        AccountMeta::new(fee_split_key(mint), false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+    ];
+    accounts.extend(
+        recipient_accounts
+            .iter()
+            .map(|account| AccountMeta::new(*account, false)),
+    );
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        DistributeTreasury::DISCRIMINATOR.as_ref(),
+        accounts,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    Ok(())
+}
+
+pub fn sweep_dust(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    mint: &Pubkey,
+    admin: &Keypair,
+    share_mint: Option<Pubkey>,
+) -> Result<(), anyhow::Error> {
+    let pool_ata = get_associated_token_address_with_program_id(pool, mint, &spl_token::ID);
+    let treasury = treasury_key(mint);
+    let treasury_ata =
+        get_associated_token_address_with_program_id(&treasury, mint, &spl_token::ID);
+    let admin_record = Pubkey::find_program_address(&[b"admin".as_ref()], &chicken::ID).0;
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        SweepDust::DISCRIMINATOR.as_ref(),
+        vec![
+            AccountMeta::new(*pool, false),
+            AccountMeta::new(pool_ata, false),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new(treasury_ata, false),
+            AccountMeta::new_readonly(admin.pubkey(), true),
+            AccountMeta::new_readonly(admin_record, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(share_mint.unwrap_or(chicken::ID), false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&admin.pubkey()),
+        &[&admin],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    assert_pool_balanced(svm, pool);
+    Ok(())
+}
+
+pub fn player_profile_key(owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"player_profile".as_ref(), owner.as_ref()], &chicken::ID).0
+}
+
+pub fn user_position_key(pool: &Pubkey, user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"user_position".as_ref(), pool.as_ref(), user.as_ref()],
+        &chicken::ID,
+    )
+    .0
+}
+
+pub fn settle(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    mint: &Pubkey,
+    cranker: &Keypair,
+    cranker_ata: Option<Pubkey>,
+    users: &[Pubkey],
+) -> Result<(), anyhow::Error> {
+    let pool_ata = get_associated_token_address_with_program_id(pool, mint, &spl_token::ID);
+    let mut accounts = vec![
+        AccountMeta::new(*pool, false),
+        AccountMeta::new_readonly(cranker.pubkey(), true),
+        AccountMeta::new(pool_ata, false),
+        AccountMeta::new(cranker_ata.unwrap_or(chicken::ID), false),
+        AccountMeta::new(*mint, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(chicken::ID, false),
+    ];
+    for user in users {
+        let user_ata = get_associated_token_address_with_program_id(user, mint, &spl_token::ID);
+        accounts.push(AccountMeta::new(user_position_key(pool, user), false));
+        accounts.push(AccountMeta::new(user_ata, false));
+        accounts.push(AccountMeta::new(*user, false));
+        accounts.push(AccountMeta::new(player_profile_key(user), false));
+    }
+    let ix = Instruction::new_with_bytes(chicken::ID, Settle::DISCRIMINATOR.as_ref(), accounts);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&cranker.pubkey()),
+        &[&cranker],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    assert_pool_balanced(svm, pool);
+    Ok(())
+}
+
+pub fn poke_pool(
+    svm: &mut LiteSVM,
+    pool: &Pubkey,
+    mint: &Pubkey,
+    keeper: &Keypair,
+) -> Result<(), anyhow::Error> {
+    let pool_ata = get_associated_token_address_with_program_id(pool, mint, &spl_token::ID);
+    let keeper_ata =
+        get_associated_token_address_with_program_id(&keeper.pubkey(), mint, &spl_token::ID);
+    let ix = Instruction::new_with_bytes(
+        chicken::ID,
+        PokePool::DISCRIMINATOR.as_ref(),
+        vec![
+            AccountMeta::new(*pool, false),
+            AccountMeta::new_readonly(keeper.pubkey(), true),
+            AccountMeta::new(pool_ata, false),
+            AccountMeta::new(keeper_ata, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&keeper.pubkey()),
+        &[&keeper],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to send transaction: {:?}", e))?;
+    assert_pool_balanced(svm, pool);
     Ok(())
 }
 
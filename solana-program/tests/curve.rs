@@ -0,0 +1,89 @@
+use chicken::{
+    curve::CURVE_ONE,
+    state::{CurvePoint, PayoutCurve},
+    MAX_CURVE_POINTS,
+};
+
+fn piecewise(table: &[(u16, u16)]) -> PayoutCurve {
+    let mut points = [CurvePoint::default(); MAX_CURVE_POINTS];
+    for (point, &(x, y)) in points.iter_mut().zip(table) {
+        *point = CurvePoint { x, y };
+    }
+    PayoutCurve::PiecewiseLinear {
+        points,
+        len: table.len() as u8,
+    }
+}
+
+fn curves() -> Vec<PayoutCurve> {
+    vec![
+        PayoutCurve::Linear,
+        PayoutCurve::Quadratic,
+        PayoutCurve::Cubic,
+        PayoutCurve::ExponentialDecay { half_life: 1 },
+        PayoutCurve::ExponentialDecay { half_life: 97 },
+        PayoutCurve::ExponentialDecay {
+            half_life: u64::MAX,
+        },
+        piecewise(&[(2_000, 0), (5_000, 8_000), (10_000, 10_000)]),
+        piecewise(&[(5_000, 5_000), (5_001, 9_000)]),
+    ]
+}
+
+#[test]
+fn test_curves_are_monotonic_and_bounded() {
+    for duration in [1, 7, 990, 1_000_000, u64::MAX] {
+        for curve in curves() {
+            assert!(curve.is_valid());
+            assert_eq!(curve.evaluate(0, duration), 0, "{curve:?}");
+            let step = (duration / 500).max(1);
+            let mut previous = 0;
+            let mut elapsed = 0u64;
+            while elapsed <= duration {
+                let value = curve.evaluate(elapsed, duration);
+                assert!(value <= CURVE_ONE, "{curve:?} at {elapsed}/{duration}");
+                assert!(value >= previous, "{curve:?} at {elapsed}/{duration}");
+                previous = value;
+                let Some(next) = elapsed.checked_add(step) else {
+                    break;
+                };
+                elapsed = next;
+            }
+            // holding past the end never pays more than holding to the end
+            assert!(curve.evaluate(u64::MAX, duration) <= CURVE_ONE);
+            assert!(curve.evaluate(u64::MAX, duration) >= previous);
+        }
+    }
+}
+
+#[test]
+fn test_curve_values() {
+    assert_eq!(PayoutCurve::Linear.evaluate(250, 1_000), 2_500);
+    assert_eq!(PayoutCurve::Quadratic.evaluate(500, 1_000), 2_500);
+    assert_eq!(PayoutCurve::Cubic.evaluate(500, 1_000), 1_250);
+    assert_eq!(PayoutCurve::Linear.evaluate(1_000, 1_000), CURVE_ONE);
+    let decay = PayoutCurve::ExponentialDecay { half_life: 100 };
+    assert_eq!(decay.evaluate(100, 10_000), 5_000);
+    assert_eq!(decay.evaluate(200, 10_000), 7_500);
+    // a short game scales the curve up so it still ends at one
+    assert_eq!(decay.evaluate(100, 1_000), 5_000 * CURVE_ONE / 9_991);
+    assert_eq!(decay.evaluate(1_000, 1_000), CURVE_ONE);
+    let table = piecewise(&[(2_000, 0), (5_000, 8_000), (10_000, 10_000)]);
+    assert_eq!(table.evaluate(100, 1_000), 0);
+    assert_eq!(table.evaluate(350, 1_000), 4_000);
+    assert_eq!(table.evaluate(1_000, 1_000), CURVE_ONE);
+    assert_eq!(PayoutCurve::Cubic.evaluate(5, 0), CURVE_ONE);
+}
+
+#[test]
+fn test_invalid_piecewise_tables() {
+    assert!(!piecewise(&[]).is_valid());
+    assert!(!piecewise(&[(5_000, 5_000), (4_000, 6_000)]).is_valid());
+    assert!(!piecewise(&[(5_000, 5_000), (6_000, 4_000)]).is_valid());
+    assert!(!piecewise(&[(10_001, 5_000)]).is_valid());
+    let mut too_long = piecewise(&[(1, 1)]);
+    if let PayoutCurve::PiecewiseLinear { len, .. } = &mut too_long {
+        *len = MAX_CURVE_POINTS as u8 + 1;
+    }
+    assert!(!too_long.is_valid());
+}
@@ -0,0 +1,134 @@
+mod common;
+use anyhow::Result;
+use chicken::{
+    actions::{bps_up, InitializePoolArgs},
+    state::{PoolMode, PositionDelegate},
+};
+use common::*;
+use litesvm_token::spl_token;
+use solana_sdk::{program_pack::Pack, signature::Keypair, signer::Signer};
+
+#[test_log::test]
+fn test_delegate_withdraws_to_owner() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 0,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (owner, owner_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (session_key, _) = setup_user(&mut ctx, 0)?;
+    deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &owner,
+        deposit_amount,
+    )?;
+    set_position_delegate(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &owner,
+        Some(PositionDelegate {
+            authority: session_key.pubkey(),
+            expiry_slot: current_clock + 600,
+        }),
+    )?;
+
+    ctx.svm.warp_to_slot(current_clock + 500);
+    let result = withdraw_as_delegate(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &ctx.mint,
+        &session_key,
+        &owner.pubkey(),
+        &session_key.pubkey(),
+    );
+    assert!(result.unwrap_err().to_string().contains("Unauthorized"));
+
+    // the position's rent goes back to the owner who funded it, not the delegate
+    let position_rent = ctx
+        .svm
+        .get_account(&user_position_key(&ctx.pool_key, &owner.pubkey()))
+        .unwrap()
+        .lamports;
+    let owner_lamports = ctx.svm.get_balance(&owner.pubkey()).unwrap();
+    withdraw_as_delegate(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &ctx.mint,
+        &session_key,
+        &owner.pubkey(),
+        &owner.pubkey(),
+    )?;
+
+    let fee = bps_up(deposit_amount, 10)?;
+    let returned = deposit_amount - fee;
+    let owner_ata = ctx.svm.get_account(&owner_ata).unwrap();
+    let owner_ata = spl_token::state::Account::unpack(&owner_ata.data).unwrap();
+    assert_eq!(owner_ata.amount, returned - bps_up(returned, 10)?);
+    assert_eq!(
+        ctx.svm.get_balance(&owner.pubkey()).unwrap(),
+        owner_lamports + position_rent
+    );
+    Ok(())
+}
+
+#[test_log::test]
+fn test_delegate_expired() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 0,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (owner, _) = setup_user(&mut ctx, 1000)?;
+    let session_key = Keypair::new();
+    ctx.svm.airdrop(&session_key.pubkey(), 10000000).unwrap();
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &owner, 1000)?;
+    set_position_delegate(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &owner,
+        Some(PositionDelegate {
+            authority: session_key.pubkey(),
+            expiry_slot: current_clock + 100,
+        }),
+    )?;
+
+    ctx.svm.warp_to_slot(current_clock + 500);
+    let result = withdraw_as_delegate(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &ctx.mint,
+        &session_key,
+        &owner.pubkey(),
+        &owner.pubkey(),
+    );
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Position delegate expired"));
+    Ok(())
+}
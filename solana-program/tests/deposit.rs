@@ -19,6 +19,7 @@ fn test_deposit_many() -> Result<()> {
         collateral_bps: 500,
         max_deposit: None,
         total_deposit_limit: None,
+        ..Default::default()
     };
     setup_pool(&mut ctx, &args)?;
     let number_of_users = 10;
@@ -39,7 +40,7 @@ fn test_deposit_many() -> Result<()> {
             chicken::state::UserPosition::deserialize(&mut &user_position_account.data[8..])
                 .unwrap();
 
-        let fee = chicken::actions::bps(random, pool.deposit_fee_bps).unwrap();
+        let fee = chicken::actions::bps_up(random, pool.deposit_fee_bps).unwrap();
         let collateral = chicken::actions::bps(random - fee, pool.collateral_bps).unwrap();
         let deposit_amount = random - fee - collateral;
 
@@ -77,6 +78,7 @@ fn test_deposit_pool_not_started() -> Result<()> {
         collateral_bps: 500,
         max_deposit: None,
         total_deposit_limit: None,
+        ..Default::default()
     };
     setup_pool(&mut ctx, &args)?;
 
@@ -101,6 +103,7 @@ fn test_deposit_pool_ended() -> Result<()> {
         collateral_bps: 500,
         max_deposit: None,
         total_deposit_limit: None,
+        ..Default::default()
     };
     setup_pool(&mut ctx, &args)?;
 
@@ -161,6 +164,7 @@ fn test_deposit_below_minimum() -> Result<()> {
         collateral_bps: 500,
         max_deposit: None,
         total_deposit_limit: None,
+        ..Default::default()
     };
     setup_pool(&mut ctx, &args)?;
     let amount = 500; // Try to deposit less than minimum
@@ -183,6 +187,7 @@ fn test_deposit_above_limit() -> Result<()> {
         collateral_bps: 500,
         max_deposit: Some(1000),
         total_deposit_limit: None,
+        ..Default::default()
     };
     setup_pool(&mut ctx, &args)?;
 
@@ -207,6 +212,7 @@ fn test_deposit_above_total_limit() -> Result<()> {
         collateral_bps: 500,
         max_deposit: None,
         total_deposit_limit: Some(5000), // Set total deposit limit
+        ..Default::default()
     };
     setup_pool(&mut ctx, &args)?;
 
@@ -235,3 +241,75 @@ fn test_deposit_above_total_limit() -> Result<()> {
         .contains("Pool Deposit limit exceeded"));
     Ok(())
 }
+
+#[test_log::test]
+fn test_deposit_max_users() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: chicken::state::PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 0,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        max_users: Some(2),
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (user1, _) = setup_user(&mut ctx, 2000)?;
+    let (user2, _) = setup_user(&mut ctx, 1000)?;
+    let (user3, _) = setup_user(&mut ctx, 1000)?;
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user1, 1000)?;
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user2, 1000)?;
+    let result = deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user3, 1000);
+    assert!(result.unwrap_err().to_string().contains("Pool is full"));
+
+    // topping up an existing position does not take a new seat
+    ctx.svm.expire_blockhash();
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user1, 1000)?;
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.users, 2);
+    Ok(())
+}
+
+#[test_log::test]
+fn test_deposit_rejects_rates_above_quote() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: chicken::state::PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 0,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (user, _) = setup_user(&mut ctx, 1000)?;
+    for (max_fee_bps, max_collateral_bps) in [(0, 500), (10, 499)] {
+        let result = deposit_with_limits(
+            &mut ctx.svm,
+            &ctx.mint,
+            &ctx.pool_key,
+            &user,
+            1000,
+            max_fee_bps,
+            max_collateral_bps,
+        );
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Pool fees exceed the accepted maximum"));
+    }
+    deposit_with_limits(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 1000, 10, 500)?;
+    Ok(())
+}
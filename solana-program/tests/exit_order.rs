@@ -0,0 +1,216 @@
+mod common;
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chicken::{
+    actions::{bps, bps_up, InitializePoolArgs},
+    state::{ExitCondition, Pool, PoolMode},
+};
+use common::*;
+use litesvm_token::spl_token;
+use solana_sdk::{program_pack::Pack, signer::Signer};
+
+#[test_log::test]
+fn test_exit_order_executes_when_users_remaining() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 0,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, _) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, _) = setup_user(&mut ctx, deposit_amount)?;
+    let (player, player_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (keeper, _) = setup_user(&mut ctx, 0)?;
+    for user in [&user1, &user2, &player] {
+        deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, user, deposit_amount)?;
+    }
+
+    let tip = 5_000;
+    place_exit_order(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &player,
+        ExitCondition::UsersAtMost(1),
+        tip,
+    )?;
+    let result = execute_exit_order(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &ctx.mint,
+        &keeper,
+        &player.pubkey(),
+    );
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Exit condition not met"));
+
+    ctx.svm.warp_to_slot(current_clock + 500);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1)?;
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user2)?;
+
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    let keeper_lamports = ctx.svm.get_balance(&keeper.pubkey()).unwrap();
+    execute_exit_order(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &ctx.mint,
+        &keeper,
+        &player.pubkey(),
+    )?;
+
+    let fee = bps_up(deposit_amount, pool.deposit_fee_bps)?;
+    let collateral = bps(deposit_amount - fee, pool.collateral_bps)?;
+    let returned = deposit_amount - fee - collateral + pool.collateral_amount;
+    let player_ata = ctx.svm.get_account(&player_ata).unwrap();
+    let player_ata = spl_token::state::Account::unpack(&player_ata.data).unwrap();
+    assert_eq!(
+        player_ata.amount,
+        returned - bps_up(returned, pool.withdraw_fee_bps)?
+    );
+    // the keeper pays the 5000 lamport signature fee out of the tip
+    assert_eq!(
+        ctx.svm.get_balance(&keeper.pubkey()).unwrap(),
+        keeper_lamports + tip - 5_000
+    );
+    let user_position = user_position_key(&ctx.pool_key, &player.pubkey());
+    let exit_order = ctx.svm.get_account(&exit_order_key(&user_position));
+    assert!(exit_order.map_or(true, |a| a.data.is_empty()));
+    Ok(())
+}
+
+#[test_log::test]
+fn test_exit_order_does_not_survive_its_position() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user, _) = setup_user(&mut ctx, deposit_amount)?;
+    let (player, _) = setup_user(&mut ctx, deposit_amount * 2)?;
+    let (keeper, _) = setup_user(&mut ctx, 0)?;
+    deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user,
+        deposit_amount,
+    )?;
+    deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &player,
+        deposit_amount,
+    )?;
+    place_exit_order(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &player,
+        ExitCondition::SlotAtLeast(current_clock + 500),
+        0,
+    )?;
+
+    // leaving by hand and coming back must not revive the old order
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &player)?;
+    ctx.svm.expire_blockhash();
+    deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &player,
+        deposit_amount,
+    )?;
+    ctx.svm.warp_to_slot(current_clock + 500);
+    let result = execute_exit_order(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &ctx.mint,
+        &keeper,
+        &player.pubkey(),
+    );
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Exit order was placed on a closed position"));
+    Ok(())
+}
+
+#[test_log::test]
+fn test_exit_order_refunds_position_rent_to_payer() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        transferable: true,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (user, _) = setup_user(&mut ctx, 1_000_000)?;
+    let (sender, _) = setup_user(&mut ctx, 1_000_000)?;
+    let (player, _) = setup_user(&mut ctx, 0)?;
+    let (keeper, _) = setup_user(&mut ctx, 0)?;
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 1_000_000)?;
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &sender, 1_000_000)?;
+    // the sender funds the player's new position, so the sender gets its rent back
+    transfer_position(&mut ctx.svm, &ctx.pool_key, &sender, &player.pubkey())?;
+    place_exit_order(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &player,
+        ExitCondition::UsersAtMost(2),
+        0,
+    )?;
+
+    let user_position = user_position_key(&ctx.pool_key, &player.pubkey());
+    let rent = ctx.svm.get_balance(&user_position).unwrap();
+    let sender_lamports = ctx.svm.get_balance(&sender.pubkey()).unwrap();
+    let player_lamports = ctx.svm.get_balance(&player.pubkey()).unwrap();
+    let exit_order_rent = ctx
+        .svm
+        .get_balance(&exit_order_key(&user_position))
+        .unwrap();
+    execute_exit_order(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &ctx.mint,
+        &keeper,
+        &player.pubkey(),
+    )?;
+    assert_eq!(
+        ctx.svm.get_balance(&sender.pubkey()).unwrap(),
+        sender_lamports + rent
+    );
+    assert_eq!(
+        ctx.svm.get_balance(&player.pubkey()).unwrap(),
+        player_lamports + exit_order_rent
+    );
+    Ok(())
+}
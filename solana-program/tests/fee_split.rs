@@ -0,0 +1,203 @@
+mod common;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chicken::state::{FeeRecipient, Treasury};
+use common::*;
+use litesvm_token::spl_token;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+struct SplitContext {
+    ctx: TestContext,
+    admin: Keypair,
+    treasury_ata: Pubkey,
+    recipients: Vec<Keypair>,
+}
+
+fn setup_split(epoch_limit: u64, treasury_amount: u64, shares: &[u16]) -> Result<SplitContext> {
+    let mut ctx = setup_test_context()?;
+    let admin = Keypair::new();
+    ctx.svm.airdrop(&admin.pubkey(), 1_000_000_000).unwrap();
+    initialize_admin(&mut ctx.svm, &admin)?;
+    let treasury_ata = initialize_treasury(&mut ctx.svm, &admin, &ctx.mint, 0)?;
+    mint_to(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.mint_authority,
+        &treasury_ata,
+        treasury_amount,
+    )?;
+    let recipients: Vec<Keypair> = shares.iter().map(|_| Keypair::new()).collect();
+    let split = recipients
+        .iter()
+        .zip(shares)
+        .map(|(recipient, &bps)| FeeRecipient {
+            owner: recipient.pubkey(),
+            bps,
+        })
+        .collect();
+    set_fee_split(&mut ctx.svm, &admin, &ctx.mint, split, epoch_limit)?;
+    Ok(SplitContext {
+        ctx,
+        admin,
+        treasury_ata,
+        recipients,
+    })
+}
+
+fn recipient_atas(split: &SplitContext) -> Vec<Pubkey> {
+    split
+        .recipients
+        .iter()
+        .map(|recipient| {
+            get_associated_token_address_with_program_id(
+                &recipient.pubkey(),
+                &split.ctx.mint,
+                &spl_token::ID,
+            )
+        })
+        .collect()
+}
+
+#[test_log::test]
+fn test_distribute_treasury_rounding() -> Result<()> {
+    let mut split = setup_split(u64::MAX, 1_001, &[7_000, 2_000, 1_000])?;
+    for recipient in &split.recipients {
+        setup_ata(&mut split.ctx.svm, &split.ctx.mint, recipient)?;
+    }
+    let atas = recipient_atas(&split);
+    let payer = Keypair::new();
+    split
+        .ctx
+        .svm
+        .airdrop(&payer.pubkey(), 1_000_000_000)
+        .unwrap();
+    distribute_treasury(&mut split.ctx.svm, &split.ctx.mint, &payer, &atas)?;
+    // every share rounds down and the first recipient picks up the remainder
+    assert_eq!(balance(&split.ctx.svm, &atas[0]), 701);
+    assert_eq!(balance(&split.ctx.svm, &atas[1]), 200);
+    assert_eq!(balance(&split.ctx.svm, &atas[2]), 100);
+    assert_eq!(balance(&split.ctx.svm, &split.treasury_ata), 0);
+
+    split.ctx.svm.expire_blockhash();
+    let result = distribute_treasury(&mut split.ctx.svm, &split.ctx.mint, &payer, &atas);
+    assert!(result.unwrap_err().to_string().contains("Nothing to sweep"));
+    Ok(())
+}
+
+#[test_log::test]
+fn test_distribute_treasury_respects_epoch_limit() -> Result<()> {
+    let mut split = setup_split(500, 1_001, &[7_000, 3_000])?;
+    for recipient in &split.recipients {
+        setup_ata(&mut split.ctx.svm, &split.ctx.mint, recipient)?;
+    }
+    let atas = recipient_atas(&split);
+    distribute_treasury(&mut split.ctx.svm, &split.ctx.mint, &split.admin, &atas)?;
+    assert_eq!(balance(&split.ctx.svm, &atas[0]), 350);
+    assert_eq!(balance(&split.ctx.svm, &atas[1]), 150);
+    assert_eq!(balance(&split.ctx.svm, &split.treasury_ata), 501);
+
+    split.ctx.svm.expire_blockhash();
+    let result = distribute_treasury(&mut split.ctx.svm, &split.ctx.mint, &split.admin, &atas);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Treasury epoch limit exceeded"));
+    // distributions run on the split's budget, the admin's is untouched
+    let treasury = split
+        .ctx
+        .svm
+        .get_account(&treasury_key(&split.ctx.mint))
+        .unwrap();
+    let treasury = Treasury::deserialize(&mut &treasury.data[8..]).unwrap();
+    assert_eq!(treasury.budget.spent, 0);
+    Ok(())
+}
+
+#[test_log::test]
+fn test_distribute_treasury_missing_recipient_ata() -> Result<()> {
+    let mut split = setup_split(u64::MAX, 1_000, &[5_000, 5_000])?;
+    setup_ata(&mut split.ctx.svm, &split.ctx.mint, &split.recipients[0])?;
+    let atas = recipient_atas(&split);
+    let result = distribute_treasury(&mut split.ctx.svm, &split.ctx.mint, &split.admin, &atas);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Fee recipient token account is missing"));
+    // nothing moves until every recipient can be paid
+    assert_eq!(balance(&split.ctx.svm, &atas[0]), 0);
+    assert_eq!(balance(&split.ctx.svm, &split.treasury_ata), 1_000);
+
+    let result = distribute_treasury(
+        &mut split.ctx.svm,
+        &split.ctx.mint,
+        &split.admin,
+        &atas[..1],
+    );
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Invalid fee split accounts"));
+    let duplicated = [atas[0], atas[0]];
+    setup_ata(&mut split.ctx.svm, &split.ctx.mint, &split.recipients[1])?;
+    let result = distribute_treasury(
+        &mut split.ctx.svm,
+        &split.ctx.mint,
+        &split.admin,
+        &duplicated,
+    );
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Invalid fee split accounts"));
+    split.ctx.svm.expire_blockhash();
+    distribute_treasury(&mut split.ctx.svm, &split.ctx.mint, &split.admin, &atas)?;
+    assert_eq!(balance(&split.ctx.svm, &atas[0]), 500);
+    assert_eq!(balance(&split.ctx.svm, &atas[1]), 500);
+    Ok(())
+}
+
+#[test_log::test]
+fn test_set_fee_split_rejects_invalid_shares() -> Result<()> {
+    let mut split = setup_split(u64::MAX, 0, &[10_000])?;
+    let owner = Pubkey::new_unique();
+    for shares in [
+        vec![],
+        vec![5_000, 4_999],
+        vec![2_000; 5],
+        vec![u16::MAX, 1],
+    ] {
+        let recipients = shares
+            .into_iter()
+            .map(|bps| FeeRecipient { owner, bps })
+            .collect();
+        let result = set_fee_split(
+            &mut split.ctx.svm,
+            &split.admin,
+            &split.ctx.mint,
+            recipients,
+            u64::MAX,
+        );
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Fee split needs 1 to 4 recipients"));
+    }
+    let outsider = Keypair::new();
+    split
+        .ctx
+        .svm
+        .airdrop(&outsider.pubkey(), 1_000_000_000)
+        .unwrap();
+    let recipients = vec![FeeRecipient { owner, bps: 10_000 }];
+    let result = set_fee_split(
+        &mut split.ctx.svm,
+        &outsider,
+        &split.ctx.mint,
+        recipients,
+        u64::MAX,
+    );
+    assert!(result.unwrap_err().to_string().contains("Unauthorized"));
+    Ok(())
+}
@@ -1,6 +1,10 @@
 mod common;
 use borsh::BorshDeserialize;
-use chicken::{actions::InitializePoolArgs, ID};
+use chicken::{
+    actions::{InitializePoolArgs, SuddenDeathArgs},
+    state::{CurvePoint, PayoutCurve, PoolMode, WeightScheme},
+    ID, MAX_CURVE_POINTS, MAX_KEEPER_BOUNTY_BPS,
+};
 use common::*;
 use litesvm::LiteSVM;
 use solana_program::pubkey::Pubkey;
@@ -18,6 +22,7 @@ fn test_init() -> anyhow::Result<()> {
         collateral_bps: 500,
         max_deposit: None,
         total_deposit_limit: None,
+        ..Default::default()
     };
     setup_pool(&mut ctx, &args)?;
     let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
@@ -41,3 +46,227 @@ fn test_init() -> anyhow::Result<()> {
     assert_eq!(chicken.collateral_mint, ctx.mint);
     Ok(())
 }
+
+fn valid_args(current_slot: u64) -> InitializePoolArgs {
+    InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_slot + 10,
+        end_time: current_slot + 1000,
+        minimum_deposit: 100,
+        collateral_bps: 500,
+        ..Default::default()
+    }
+}
+
+fn assert_init_rejected(args: &InitializePoolArgs, message: &str) -> anyhow::Result<()> {
+    let mut ctx = setup_test_context()?;
+    ctx.svm.warp_to_slot(100);
+    let result = setup_pool(&mut ctx, args);
+    assert!(
+        result.as_ref().unwrap_err().to_string().contains(message),
+        "expected {message:?}, got {result:?}"
+    );
+    Ok(())
+}
+
+#[test_log::test]
+fn test_init_rejects_invalid_args() -> anyhow::Result<()> {
+    let slot = 100;
+    // the payout falls back from 6000 to 4000 bps
+    let mut falling_curve = [CurvePoint::default(); MAX_CURVE_POINTS];
+    falling_curve[0] = CurvePoint { x: 5_000, y: 6_000 };
+    falling_curve[1] = CurvePoint {
+        x: 10_000,
+        y: 4_000,
+    };
+    let cases: Vec<(InitializePoolArgs, &str)> = vec![
+        (
+            InitializePoolArgs {
+                end_time: slot + 10,
+                ..valid_args(slot)
+            },
+            "Pool must start before it ends",
+        ),
+        (
+            InitializePoolArgs {
+                start_time: slot - 1,
+                ..valid_args(slot)
+            },
+            "Pool start is in the past",
+        ),
+        (
+            InitializePoolArgs {
+                collateral_bps: 10_001,
+                ..valid_args(slot)
+            },
+            "Collateral bps above 10000",
+        ),
+        (
+            InitializePoolArgs {
+                keeper_bounty_bps: MAX_KEEPER_BOUNTY_BPS + 1,
+                ..valid_args(slot)
+            },
+            "Keeper bounty above the protocol maximum",
+        ),
+        (
+            InitializePoolArgs {
+                max_deposit: Some(99),
+                ..valid_args(slot)
+            },
+            "Max deposit is below the minimum deposit",
+        ),
+        (
+            InitializePoolArgs {
+                total_deposit_limit: Some(99),
+                ..valid_args(slot)
+            },
+            "Total deposit limit is below the minimum deposit",
+        ),
+        (
+            InitializePoolArgs {
+                max_users: Some(2),
+                min_users: 3,
+                ..valid_args(slot)
+            },
+            "Invalid user limits",
+        ),
+        (
+            InitializePoolArgs {
+                max_users: Some(0),
+                ..valid_args(slot)
+            },
+            "Invalid user limits",
+        ),
+        (
+            InitializePoolArgs {
+                share_tokens: true,
+                max_users: Some(10),
+                ..valid_args(slot)
+            },
+            "Share tokens are not supported for this pool",
+        ),
+        (
+            InitializePoolArgs {
+                share_tokens: true,
+                transferable: true,
+                ..valid_args(slot)
+            },
+            "Share tokens are not supported for this pool",
+        ),
+        (
+            InitializePoolArgs {
+                share_tokens: true,
+                pool_mode: PoolMode::TimeBased,
+                ..valid_args(slot)
+            },
+            "Share tokens are not supported for this pool",
+        ),
+        (
+            InitializePoolArgs {
+                share_tokens: true,
+                position_receipts: true,
+                ..valid_args(slot)
+            },
+            "Share tokens are not supported for this pool",
+        ),
+        (
+            InitializePoolArgs {
+                share_tokens: true,
+                min_users: 2,
+                ..valid_args(slot)
+            },
+            "Share tokens are not supported for this pool",
+        ),
+        (
+            InitializePoolArgs {
+                pool_mode: PoolMode::Survival { interval: 0 },
+                ..valid_args(slot)
+            },
+            "Survival check-in interval must be positive",
+        ),
+        (
+            InitializePoolArgs {
+                pool_mode: PoolMode::Survival { interval: 100 },
+                position_receipts: true,
+                ..valid_args(slot)
+            },
+            "Position receipts are not supported for this pool",
+        ),
+        (
+            InitializePoolArgs {
+                pool_mode: PoolMode::LastNOut {
+                    winners: 0,
+                    split: WeightScheme::Even,
+                },
+                ..valid_args(slot)
+            },
+            "Last-n-out pools need at least one winner",
+        ),
+        (
+            InitializePoolArgs {
+                pool_mode: PoolMode::KingOfTheHill {
+                    extension: 50,
+                    max_end_time: slot + 2000,
+                    winner_share_bps: 0,
+                },
+                ..valid_args(slot)
+            },
+            "Invalid king-of-the-hill parameters",
+        ),
+        (
+            InitializePoolArgs {
+                sudden_death: Some(SuddenDeathArgs {
+                    earliest_end: slot + 5,
+                    ..Default::default()
+                }),
+                ..valid_args(slot)
+            },
+            "Invalid sudden-death parameters",
+        ),
+        (
+            InitializePoolArgs {
+                pool_mode: PoolMode::TimeBased,
+                sudden_death: Some(SuddenDeathArgs {
+                    earliest_end: slot + 500,
+                    ..Default::default()
+                }),
+                ..valid_args(slot)
+            },
+            "Invalid sudden-death parameters",
+        ),
+        (
+            InitializePoolArgs {
+                share_tokens: true,
+                sudden_death: Some(SuddenDeathArgs {
+                    earliest_end: slot + 500,
+                    ..Default::default()
+                }),
+                ..valid_args(slot)
+            },
+            "Invalid sudden-death parameters",
+        ),
+        (
+            InitializePoolArgs {
+                payout_curve: PayoutCurve::Quadratic,
+                ..valid_args(slot)
+            },
+            "Invalid payout curve",
+        ),
+        (
+            InitializePoolArgs {
+                pool_mode: PoolMode::TimeBased,
+                payout_curve: PayoutCurve::PiecewiseLinear {
+                    points: falling_curve,
+                    len: 2,
+                },
+                ..valid_args(slot)
+            },
+            "Invalid payout curve",
+        ),
+    ];
+    for (args, message) in cases {
+        assert_init_rejected(&args, message)?;
+    }
+    Ok(())
+}
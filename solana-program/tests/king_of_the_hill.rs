@@ -0,0 +1,123 @@
+mod common;
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chicken::{
+    actions::{bps, bps_up, InitializePoolArgs},
+    state::{Pool, PoolMode},
+};
+use common::*;
+use litesvm_token::spl_token;
+use solana_sdk::{program_pack::Pack, signer::Signer};
+
+#[test_log::test]
+fn test_king_of_the_hill_last_depositor_wins() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::KingOfTheHill {
+            extension: 50,
+            max_end_time: current_clock + 200,
+            winner_share_bps: 5_000,
+        },
+        start_time: current_clock + 10,
+        end_time: current_clock + 100,
+        minimum_deposit: 1_000,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, user1_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, user2_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (user3, _) = setup_user(&mut ctx, 500)?;
+    deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user1,
+        deposit_amount,
+    )?;
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.end_time, current_clock + 150);
+    assert_eq!(pool.last_depositor, user1.pubkey());
+
+    deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user2,
+        deposit_amount,
+    )?;
+    // below the minimum, so neither the timer nor the crown moves
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user3, 500)?;
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.end_time, current_clock + 200);
+    assert_eq!(pool.last_depositor, user2.pubkey());
+    let pot = pool.collateral_amount;
+
+    ctx.svm.warp_to_slot(current_clock + 201);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1)?;
+    let fees_before = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let fees_before = Pool::deserialize(&mut &fees_before.data[8..])
+        .unwrap()
+        .fee_amount;
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user2)?;
+
+    let fee = bps_up(deposit_amount, pool.deposit_fee_bps)?;
+    let collateral = bps(deposit_amount - fee, pool.collateral_bps)?;
+    let principal = deposit_amount - fee - collateral;
+    let user1_ata = ctx.svm.get_account(&user1_ata).unwrap();
+    let user1_ata = spl_token::state::Account::unpack(&user1_ata.data).unwrap();
+    assert_eq!(
+        user1_ata.amount,
+        principal - bps_up(principal, pool.withdraw_fee_bps)?
+    );
+    let winnings = principal + bps(pot, 5_000)?;
+    let user2_ata = ctx.svm.get_account(&user2_ata).unwrap();
+    let user2_ata = spl_token::state::Account::unpack(&user2_ata.data).unwrap();
+    assert_eq!(
+        user2_ata.amount,
+        winnings - bps_up(winnings, pool.withdraw_fee_bps)?
+    );
+
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.collateral_amount, 0);
+    assert_eq!(pool.last_depositor, Default::default());
+    // the part of the pot the winner does not take is booked as protocol fees
+    assert_eq!(
+        pool.fee_amount - fees_before,
+        pot - bps(pot, 5_000)? + bps_up(winnings, pool.withdraw_fee_bps)?
+    );
+    Ok(())
+}
+
+#[test_log::test]
+fn test_king_of_the_hill_rejects_cap_before_end() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::KingOfTheHill {
+            extension: 50,
+            max_end_time: current_clock + 50,
+            winner_share_bps: 5_000,
+        },
+        start_time: current_clock + 10,
+        end_time: current_clock + 100,
+        ..Default::default()
+    };
+    let result = setup_pool(&mut ctx, &args);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Invalid king-of-the-hill parameters"));
+    Ok(())
+}
@@ -0,0 +1,158 @@
+mod common;
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chicken::{
+    actions::{bps, bps_up, InitializePoolArgs},
+    state::{Pool, PoolMode, WeightScheme},
+};
+use common::*;
+
+#[test_log::test]
+fn test_last_n_out_even_split() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastNOut {
+            winners: 2,
+            split: WeightScheme::Even,
+        },
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, user1_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, user2_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (user3, user3_ata) = setup_user(&mut ctx, deposit_amount)?;
+    for user in [&user1, &user2, &user3] {
+        deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, user, deposit_amount)?;
+    }
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    let fee = bps_up(deposit_amount, pool.deposit_fee_bps)?;
+    let collateral = bps(deposit_amount - fee, pool.collateral_bps)?;
+    let principal = deposit_amount - fee - collateral;
+    let pot = collateral * 3;
+
+    ctx.svm.warp_to_slot(current_clock + 500);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1)?;
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user2)?;
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user3)?;
+
+    let payout = |amount: u64| amount - bps_up(amount, pool.withdraw_fee_bps).unwrap();
+    assert_eq!(balance(&ctx.svm, &user1_ata), payout(principal));
+    assert_eq!(balance(&ctx.svm, &user2_ata), payout(principal + pot / 2));
+    assert_eq!(
+        balance(&ctx.svm, &user3_ata),
+        payout(principal + pot - pot / 2)
+    );
+
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.collateral_amount, 0);
+    assert_eq!(pool.exits, 3);
+    assert_eq!(pool.winner_count, 2);
+    Ok(())
+}
+
+#[test_log::test]
+fn test_last_n_out_split_by_deposit() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastNOut {
+            winners: 2,
+            split: WeightScheme::ByDeposit,
+        },
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (small, small_ata) = setup_user(&mut ctx, 1_000_000)?;
+    let (large, large_ata) = setup_user(&mut ctx, 3_000_000)?;
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &small, 1_000_000)?;
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &large, 3_000_000)?;
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    let net = |amount: u64| {
+        let fee = bps_up(amount, pool.deposit_fee_bps).unwrap();
+        let collateral = bps(amount - fee, pool.collateral_bps).unwrap();
+        (amount - fee - collateral, collateral)
+    };
+    let (small_principal, small_collateral) = net(1_000_000);
+    let (large_principal, large_collateral) = net(3_000_000);
+    let pot = small_collateral + large_collateral;
+
+    ctx.svm.warp_to_slot(current_clock + 500);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &small)?;
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &large)?;
+
+    let small_share = pot * small_principal / (small_principal + large_principal);
+    let payout = |amount: u64| amount - bps_up(amount, pool.withdraw_fee_bps).unwrap();
+    assert_eq!(
+        balance(&ctx.svm, &small_ata),
+        payout(small_principal + small_share)
+    );
+    assert_eq!(
+        balance(&ctx.svm, &large_ata),
+        payout(large_principal + pot - small_share)
+    );
+    Ok(())
+}
+
+#[test_log::test]
+fn test_last_n_out_rejects_deposits_once_winners_are_set() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastNOut {
+            winners: 2,
+            split: WeightScheme::Even,
+        },
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, _) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, _) = setup_user(&mut ctx, deposit_amount)?;
+    let (user3, _) = setup_user(&mut ctx, deposit_amount)?;
+    let (late, _) = setup_user(&mut ctx, deposit_amount)?;
+    for user in [&user1, &user2, &user3] {
+        deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, user, deposit_amount)?;
+    }
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1)?;
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user2)?;
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.winner_count, 2);
+
+    // the pot is already split among the winners, a newcomer could only dilute it
+    let result = deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &late,
+        deposit_amount,
+    );
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Winners have already been decided"));
+    Ok(())
+}
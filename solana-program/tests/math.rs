@@ -0,0 +1,175 @@
+use anchor_lang::prelude::Pubkey;
+use chicken::{
+    actions::{
+        assert_pool_active, assert_pool_withdrawable, book_deposit, book_withdraw, bps, bps_up,
+        extend_pool_timer, update_pool_state,
+    },
+    state::{PayoutCurve, Pool, PoolMode, PoolPhase, PoolState, UserPosition, WeightScheme},
+    DEPOSIT_FEE_BPS, MAX_POOL_PHASES, WITHDRAW_FEE_BPS,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const PLAYERS: usize = 6;
+
+fn pool(mode: PoolMode) -> Pool {
+    Pool {
+        deposit_fee_bps: DEPOSIT_FEE_BPS,
+        withdraw_fee_bps: WITHDRAW_FEE_BPS,
+        collateral_bps: 500,
+        mode,
+        start_time: 10,
+        end_time: 1_000,
+        ..Default::default()
+    }
+}
+
+fn pools() -> Vec<Pool> {
+    let mut phased = pool(PoolMode::LastOutWinner);
+    let mut phases = [PoolPhase::default(); MAX_POOL_PHASES];
+    phases[0] = PoolPhase {
+        slot_offset: 0,
+        exit_penalty_bps: 2_500,
+        deposit_allowed: true,
+    };
+    phases[1] = PoolPhase {
+        slot_offset: 500,
+        exit_penalty_bps: 10_000,
+        deposit_allowed: false,
+    };
+    phased.phases = phases;
+    phased.phase_count = 2;
+    let mut refunding = pool(PoolMode::LastOutWinner);
+    refunding.min_users = PLAYERS as u32 + 1;
+    let mut time_based = pool(PoolMode::TimeBased);
+    time_based.payout_curve = PayoutCurve::Quadratic;
+    vec![
+        pool(PoolMode::LastOutWinner),
+        phased,
+        refunding,
+        pool(PoolMode::TimeBased),
+        time_based,
+        pool(PoolMode::KingOfTheHill {
+            extension: 50,
+            max_end_time: 2_000,
+            winner_share_bps: 8_000,
+        }),
+        pool(PoolMode::LastNOut {
+            winners: 2,
+            split: WeightScheme::ByDeposit,
+        }),
+        pool(PoolMode::Survival { interval: 200 }),
+    ]
+}
+
+fn amount(rng: &mut StdRng) -> u64 {
+    match rng.gen_range(0..4) {
+        0 => rng.gen_range(0..100),
+        1 => rng.gen_range(0..1_000_000_000),
+        2 => rng.gen_range(u64::MAX / 4..=u64::MAX),
+        _ => rng.gen(),
+    }
+}
+
+/// Drives the pool accounting with random deposits and withdrawals. Every step either applies
+/// cleanly or fails with an error, and the vault always matches what the pool has booked.
+fn run(mut pool: Pool, rng: &mut StdRng) {
+    let players: Vec<Pubkey> = (0..PLAYERS).map(|_| Pubkey::new_unique()).collect();
+    let mut positions: Vec<Option<UserPosition>> = vec![None; PLAYERS];
+    let mut vault = 0u64;
+    let mut slot = pool.start_time;
+    for _ in 0..64 {
+        slot += rng.gen_range(0..60);
+        if update_pool_state(&mut pool, slot).is_err() {
+            continue;
+        }
+        let player = rng.gen_range(0..PLAYERS);
+        if rng.gen_bool(0.6) {
+            let amount = amount(rng);
+            if assert_pool_active(&pool).is_err() || vault.checked_add(amount).is_none() {
+                continue;
+            }
+            let mut next_pool = pool.clone();
+            let mut position = positions[player].clone().unwrap_or_default();
+            if book_deposit(&mut next_pool, &mut position, amount).is_err() {
+                continue;
+            }
+            if position.deposit_time == 0 {
+                position.deposit_time = slot;
+            }
+            position.owner = players[player];
+            position.last_check_in = slot;
+            extend_pool_timer(&mut next_pool, position.owner, amount);
+            pool = next_pool;
+            positions[player] = Some(position);
+            vault += amount;
+        } else if let Some(position) = positions[player].clone() {
+            if assert_pool_withdrawable(&pool).is_err() {
+                continue;
+            }
+            let mut next_pool = pool.clone();
+            let Ok((final_amount, _)) = book_withdraw(&mut next_pool, &position, slot) else {
+                continue;
+            };
+            assert!(final_amount <= vault, "{:?}", pool.mode);
+            pool = next_pool;
+            positions[player] = None;
+            vault -= final_amount;
+        }
+        assert_eq!(
+            vault as u128,
+            pool.total_principal as u128 + pool.collateral_amount as u128 + pool.fee_amount as u128,
+            "{:?}",
+            pool.mode
+        );
+    }
+}
+
+#[test]
+fn test_random_deposit_withdraw_sequences_never_panic() {
+    let mut rng = StdRng::seed_from_u64(0x0c41c3e5);
+    for pool in pools() {
+        for _ in 0..200 {
+            run(pool.clone(), &mut rng);
+        }
+    }
+}
+
+#[test]
+fn test_deposit_overflow_is_an_error() {
+    let mut pool = pool(PoolMode::LastOutWinner);
+    let mut position = UserPosition::default();
+    book_deposit(&mut pool, &mut position, u64::MAX).unwrap();
+    position.deposit_time = 1;
+    let err = book_deposit(&mut pool, &mut position, u64::MAX).unwrap_err();
+    assert!(err.to_string().contains("Math overflow"));
+}
+
+#[test]
+fn test_fees_round_up_and_payouts_round_down() {
+    assert_eq!(bps(1_999, 10).unwrap(), 1);
+    assert_eq!(bps_up(1_999, 10).unwrap(), 2);
+    assert_eq!(bps_up(2_000, 10).unwrap(), 2);
+    assert_eq!(bps_up(0, 10).unwrap(), 0);
+    assert_eq!(bps_up(u64::MAX, 10_000).unwrap(), u64::MAX);
+}
+
+#[test]
+fn test_last_withdrawer_takes_dust() {
+    let mut pool = pool(PoolMode::LastOutWinner);
+    let position = UserPosition {
+        deposit_amount: 1_000,
+        collateral_amount: 50,
+        ..Default::default()
+    };
+    pool.state = PoolState::Ended;
+    pool.users = 1;
+    pool.total_principal = position.deposit_amount + 3;
+    pool.collateral_amount = position.collateral_amount + 2;
+    pool.fee_amount = 7;
+    let (final_amount, fee) = book_withdraw(&mut pool, &position, 2_000).unwrap();
+    assert_eq!(final_amount + fee, 1_055);
+    assert_eq!(fee, bps_up(1_055, WITHDRAW_FEE_BPS).unwrap());
+    assert_eq!(pool.total_principal, 0);
+    assert_eq!(pool.collateral_amount, 0);
+    assert_eq!(pool.fee_amount, 7 + fee);
+}
@@ -0,0 +1,274 @@
+mod common;
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chicken::{
+    actions::{bps, bps_up, InitializePoolArgs},
+    state::{PlayerProfile, Pool, PoolMode, PoolPhase, PoolState},
+};
+use common::*;
+use litesvm_token::spl_token;
+use solana_sdk::{program_pack::Pack, signature::Keypair, signer::Signer};
+
+#[test_log::test]
+fn test_pool_below_min_users_refunds_everything() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        min_users: 3,
+        phases: vec![
+            PoolPhase {
+                slot_offset: 0,
+                exit_penalty_bps: 10_000,
+                deposit_allowed: true,
+            },
+            PoolPhase {
+                slot_offset: 100,
+                exit_penalty_bps: 10_000,
+                deposit_allowed: false,
+            },
+        ],
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, user1_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, user2_ata) = setup_user(&mut ctx, deposit_amount)?;
+    deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user1,
+        deposit_amount,
+    )?;
+    deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user2,
+        deposit_amount,
+    )?;
+
+    // deposits close when the second phase starts
+    ctx.svm.warp_to_slot(current_clock + 110);
+    let (late, _) = setup_user(&mut ctx, deposit_amount)?;
+    let result = deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &late,
+        deposit_amount,
+    );
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Pool is refunding"));
+
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1)?;
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user2)?;
+    for ata in [user1_ata, user2_ata] {
+        let account = ctx.svm.get_account(&ata).unwrap();
+        let account = spl_token::state::Account::unpack(&account.data).unwrap();
+        assert_eq!(account.amount, deposit_amount);
+    }
+
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.state, PoolState::Refunding);
+    assert_eq!(pool.users, 0);
+    assert_eq!(pool.fee_amount, 0);
+    assert_eq!(pool.collateral_amount, 0);
+    let pool_ata = ctx.svm.get_account(&ctx.pool_ata).unwrap();
+    let pool_ata = spl_token::state::Account::unpack(&pool_ata.data).unwrap();
+    assert_eq!(pool_ata.amount, 0);
+    // a refund is neither a win nor a loss
+    let profile = ctx
+        .svm
+        .get_account(&player_profile_key(&user1.pubkey()))
+        .unwrap();
+    let profile = PlayerProfile::deserialize(&mut &profile.data[8..]).unwrap();
+    assert_eq!((profile.wins, profile.losses), (0, 0));
+    assert_eq!(profile.total_fees_paid, 0);
+    Ok(())
+}
+
+fn phased_args(current_clock: u64, min_users: u32) -> InitializePoolArgs {
+    InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        min_users,
+        phases: vec![
+            PoolPhase {
+                slot_offset: 0,
+                exit_penalty_bps: 10_000,
+                deposit_allowed: true,
+            },
+            PoolPhase {
+                slot_offset: 100,
+                exit_penalty_bps: 10_000,
+                deposit_allowed: false,
+            },
+        ],
+        ..Default::default()
+    }
+}
+
+#[test_log::test]
+fn test_pool_keeps_running_when_players_leave_after_min_users_met() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    setup_pool(&mut ctx, &phased_args(current_clock, 2))?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, _) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, user2_ata) = setup_user(&mut ctx, deposit_amount)?;
+    deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user1,
+        deposit_amount,
+    )?;
+    deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user2,
+        deposit_amount,
+    )?;
+
+    // both players were in when joining closed, so dropping below the minimum afterwards is play
+    ctx.svm.warp_to_slot(current_clock + 110);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1)?;
+    ctx.svm.warp_to_slot(current_clock + 120);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user2)?;
+
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.state, PoolState::Started);
+    assert_eq!(pool.joined, 2);
+    // the last one out won the pot instead of being refunded
+    assert!(balance(&ctx.svm, &user2_ata) > deposit_amount);
+    Ok(())
+}
+
+#[test_log::test]
+fn test_remove_refunded_pool() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let admin = Keypair::new();
+    ctx.svm.airdrop(&admin.pubkey(), 1_000_000_000).unwrap();
+    initialize_admin(&mut ctx.svm, &admin)?;
+    initialize_treasury(&mut ctx.svm, &admin, &ctx.mint, 0)?;
+    setup_pool(&mut ctx, &phased_args(current_clock, 3))?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, _) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, user2_ata) = setup_user(&mut ctx, deposit_amount)?;
+    deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user1,
+        deposit_amount,
+    )?;
+    deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user2,
+        deposit_amount,
+    )?;
+
+    // user 1 leaves while joining is still open and forfeits its collateral
+    ctx.svm.warp_to_slot(current_clock + 50);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1)?;
+
+    ctx.svm.warp_to_slot(current_clock + 110);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user2)?;
+    let forfeited = bps(deposit_amount - bps_up(deposit_amount, 10)?, 500)?;
+    assert_eq!(balance(&ctx.svm, &user2_ata), deposit_amount + forfeited);
+
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.state, PoolState::Refunding);
+    assert_eq!(pool.collateral_amount, 0);
+    assert_eq!(pool.total_principal, 0);
+
+    sweep_fees_to_treasury(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user2)?;
+    assert_eq!(balance(&ctx.svm, &ctx.pool_ata), 0);
+    remove_pool(&mut ctx.svm, &ctx.pool_key, &ctx.creator, None)?;
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.state, PoolState::Removed);
+    Ok(())
+}
+
+#[test_log::test]
+fn test_plain_pool_keeps_running_when_players_leave_mid_game() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        min_users: 3,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let mut users = Vec::new();
+    for _ in 0..5 {
+        let (user, user_ata) = setup_user(&mut ctx, deposit_amount)?;
+        deposit(
+            &mut ctx.svm,
+            &ctx.mint,
+            &ctx.pool_key,
+            &user,
+            deposit_amount,
+        )?;
+        users.push((user, user_ata));
+    }
+
+    // joining only closes at the end, by which time three players have left
+    ctx.svm.warp_to_slot(current_clock + 500);
+    for (user, _) in &users[..3] {
+        withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, user)?;
+    }
+    ctx.svm.warp_to_slot(current_clock + 1001);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &users[3].0)?;
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &users[4].0)?;
+
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.state, PoolState::Ended);
+    let fee = bps_up(deposit_amount, 10)?;
+    let collateral = bps(deposit_amount - fee, 500)?;
+    let principal = deposit_amount - fee - collateral;
+    // the first of the two left standing is not the last out, the second takes all the collateral
+    assert_eq!(
+        balance(&ctx.svm, &users[3].1),
+        principal - bps_up(principal, 10)?
+    );
+    let last_out = principal + collateral * 5;
+    assert_eq!(
+        balance(&ctx.svm, &users[4].1),
+        last_out - bps_up(last_out, 10)?
+    );
+    Ok(())
+}
@@ -0,0 +1,116 @@
+mod common;
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chicken::{
+    actions::{bps, bps_up, InitializePoolArgs},
+    state::{Pool, PoolMode, PoolPhase},
+};
+use common::*;
+use litesvm_token::spl_token;
+use solana_sdk::program_pack::Pack;
+
+fn phased_args(current_clock: u64, phases: Vec<PoolPhase>) -> InitializePoolArgs {
+    InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        phases,
+        ..Default::default()
+    }
+}
+
+#[test_log::test]
+fn test_phase_penalty_rises_over_time() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let phases = vec![
+        PoolPhase {
+            slot_offset: 0,
+            exit_penalty_bps: 2_000,
+            deposit_allowed: true,
+        },
+        PoolPhase {
+            slot_offset: 100,
+            exit_penalty_bps: 5_000,
+            deposit_allowed: true,
+        },
+        PoolPhase {
+            slot_offset: 200,
+            exit_penalty_bps: 10_000,
+            deposit_allowed: false,
+        },
+    ];
+    setup_pool(&mut ctx, &phased_args(current_clock, phases))?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, user1_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, user2_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (user3, user3_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (late, _) = setup_user(&mut ctx, deposit_amount)?;
+    for user in [&user1, &user2, &user3] {
+        deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, user, deposit_amount)?;
+    }
+
+    ctx.svm.warp_to_slot(current_clock + 50);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1)?;
+    ctx.svm.warp_to_slot(current_clock + 150);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user2)?;
+    ctx.svm.warp_to_slot(current_clock + 250);
+    let result = deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &late,
+        deposit_amount,
+    );
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Deposits are closed in this phase"));
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user3)?;
+
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    let fee = bps_up(deposit_amount, pool.deposit_fee_bps)?;
+    let collateral = bps(deposit_amount - fee, pool.collateral_bps)?;
+    let principal = deposit_amount - fee - collateral;
+    let user1_refund = collateral - bps_up(collateral, 2_000)?;
+    let user2_refund = collateral - bps_up(collateral, 5_000)?;
+    let payout = |amount: u64| amount - bps_up(amount, pool.withdraw_fee_bps).unwrap();
+    let balance = |ata| {
+        let account = ctx.svm.get_account(ata).unwrap();
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    };
+    assert_eq!(balance(&user1_ata), payout(principal + user1_refund));
+    assert_eq!(balance(&user2_ata), payout(principal + user2_refund));
+    assert_eq!(
+        balance(&user3_ata),
+        payout(principal + collateral * 3 - user1_refund - user2_refund)
+    );
+    assert_eq!(pool.collateral_amount, 0);
+    Ok(())
+}
+
+#[test_log::test]
+fn test_phases_must_start_at_zero_and_increase() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let phase = |slot_offset| PoolPhase {
+        slot_offset,
+        exit_penalty_bps: 1_000,
+        deposit_allowed: true,
+    };
+    for phases in [vec![phase(10)], vec![phase(0), phase(100), phase(100)]] {
+        let result = setup_pool(&mut ctx, &phased_args(current_clock, phases));
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Invalid pool phases"));
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+mod common;
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chicken::{
+    actions::{bps, bps_up, InitializePoolArgs},
+    state::{PlayerProfile, PoolMode},
+};
+use common::*;
+use solana_sdk::signer::Signer;
+
+#[test_log::test]
+fn test_player_profile_records_wins_and_losses() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 0,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (loser, _) = setup_user(&mut ctx, deposit_amount)?;
+    let (winner, _) = setup_user(&mut ctx, deposit_amount)?;
+    deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &loser,
+        deposit_amount,
+    )?;
+    deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &winner,
+        deposit_amount,
+    )?;
+
+    ctx.svm.warp_to_slot(current_clock + 200);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &loser)?;
+    ctx.svm.warp_to_slot(current_clock + 511);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &winner)?;
+
+    let deposit_fee = bps_up(deposit_amount, 10)?;
+    let collateral = bps(deposit_amount - deposit_fee, 500)?;
+    let position = deposit_amount - deposit_fee - collateral;
+
+    let loser_profile = ctx
+        .svm
+        .get_account(&player_profile_key(&loser.pubkey()))
+        .unwrap();
+    let loser_profile = PlayerProfile::deserialize(&mut &loser_profile.data[8..]).unwrap();
+    assert_eq!(loser_profile.owner, loser.pubkey());
+    assert_eq!(loser_profile.pools_joined, 1);
+    assert_eq!(loser_profile.wins, 0);
+    assert_eq!(loser_profile.losses, 1);
+    assert_eq!(loser_profile.total_deposited, deposit_amount);
+    assert_eq!(loser_profile.total_won, 0);
+    assert_eq!(
+        loser_profile.total_fees_paid,
+        deposit_fee + bps_up(position, 10)?
+    );
+    assert_eq!(loser_profile.longest_hold, 189);
+
+    let winner_profile = ctx
+        .svm
+        .get_account(&player_profile_key(&winner.pubkey()))
+        .unwrap();
+    let winner_profile = PlayerProfile::deserialize(&mut &winner_profile.data[8..]).unwrap();
+    assert_eq!(winner_profile.pools_joined, 1);
+    assert_eq!(winner_profile.wins, 1);
+    assert_eq!(winner_profile.losses, 0);
+    assert_eq!(winner_profile.total_won, collateral);
+    assert_eq!(
+        winner_profile.total_fees_paid,
+        deposit_fee + bps_up(position + collateral * 2, 10)?
+    );
+    assert_eq!(winner_profile.longest_hold, 500);
+    Ok(())
+}
@@ -0,0 +1,120 @@
+mod common;
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chicken::{
+    actions::{bps, bps_up, InitializePoolArgs},
+    state::{Pool, PoolMode, PoolState},
+    DEPOSIT_FEE_BPS,
+};
+use common::*;
+use litesvm_token::spl_token;
+use solana_sdk::program_pack::Pack;
+
+#[test_log::test]
+fn test_poke_pool_advances_state_and_pays_bounty() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 0,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        keeper_bounty_bps: 1_000,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+    let (keeper, keeper_ata) = setup_user(&mut ctx, 0)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    poke_pool(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &keeper)?;
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.state, PoolState::Started);
+
+    let (user, _) = setup_user(&mut ctx, 1_000_000)?;
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 1_000_000)?;
+
+    ctx.svm.warp_to_slot(current_clock + 1001);
+    let pool_before = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool_before = Pool::deserialize(&mut &pool_before.data[8..]).unwrap();
+    poke_pool(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &keeper)?;
+
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    let bounty = bps(pool_before.fee_amount, 1_000)?;
+    assert_eq!(pool.state, PoolState::Ended);
+    assert_eq!(pool.fee_amount, pool_before.fee_amount - bounty);
+    let keeper_ata = ctx.svm.get_account(&keeper_ata).unwrap();
+    let keeper_ata = spl_token::state::Account::unpack(&keeper_ata.data).unwrap();
+    assert_eq!(keeper_ata.amount, bounty);
+    Ok(())
+}
+
+#[test_log::test]
+fn test_poke_pool_unchanged() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 0,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        keeper_bounty_bps: 1_000,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+    let (keeper, _) = setup_user(&mut ctx, 0)?;
+
+    let result = poke_pool(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &keeper);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Pool state unchanged"));
+    Ok(())
+}
+
+#[test_log::test]
+fn test_poke_pool_into_refunding_pays_no_bounty() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        keeper_bounty_bps: 1_000,
+        min_users: 2,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+    let (keeper, keeper_ata) = setup_user(&mut ctx, 0)?;
+    let (user, user_ata) = setup_user(&mut ctx, 1_000_000)?;
+    ctx.svm.warp_to_slot(current_clock + 11);
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 1_000_000)?;
+
+    ctx.svm.warp_to_slot(current_clock + 1001);
+    poke_pool(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &keeper)?;
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.state, PoolState::Refunding);
+    assert_eq!(pool.fee_amount, bps_up(1_000_000, DEPOSIT_FEE_BPS)?);
+    let keeper_ata = ctx.svm.get_account(&keeper_ata).unwrap();
+    let keeper_ata = spl_token::state::Account::unpack(&keeper_ata.data).unwrap();
+    assert_eq!(keeper_ata.amount, 0);
+
+    // the refund still covers the deposit fee
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user)?;
+    let user_ata = ctx.svm.get_account(&user_ata).unwrap();
+    let user_ata = spl_token::state::Account::unpack(&user_ata.data).unwrap();
+    assert_eq!(user_ata.amount, 1_000_000);
+    Ok(())
+}
@@ -0,0 +1,83 @@
+mod common;
+use anyhow::Result;
+use chicken::{
+    actions::{bps, bps_up, InitializePoolArgs},
+    state::PoolMode,
+};
+use common::*;
+use litesvm_token::spl_token;
+use solana_sdk::program_pack::Pack;
+
+#[test_log::test]
+fn test_quotes_match_deposit_and_withdraw() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, _) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, user2_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let quote = quote_deposit(&mut ctx.svm, &ctx.pool_key, &user1, deposit_amount)?;
+    let fee = bps_up(deposit_amount, 10)?;
+    let collateral = bps(deposit_amount - fee, 500)?;
+    assert_eq!(quote.fee, fee);
+    assert_eq!(quote.collateral, collateral);
+    assert_eq!(quote.principal, deposit_amount - fee - collateral);
+    for user in [&user1, &user2] {
+        deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, user, deposit_amount)?;
+    }
+
+    ctx.svm.warp_to_slot(current_clock + 500);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1)?;
+    let quote = quote_withdraw(&mut ctx.svm, &ctx.pool_key, &user2)?;
+    assert_eq!(quote.principal, deposit_amount - fee - collateral);
+    assert_eq!(quote.collateral, collateral * 2);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user2)?;
+    let account = ctx.svm.get_account(&user2_ata).unwrap();
+    let balance = spl_token::state::Account::unpack(&account.data)?.amount;
+    assert_eq!(balance, quote.final_amount);
+    assert_eq!(
+        quote.final_amount + quote.fee,
+        quote.principal + quote.collateral
+    );
+    Ok(())
+}
+
+#[test_log::test]
+fn test_quote_deposit_runs_deposit_checks() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 100,
+        collateral_bps: 500,
+        max_deposit: Some(1000),
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (user, _) = setup_user(&mut ctx, 1000)?;
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 600)?;
+    // the existing position counts towards the per-user limit
+    let result = quote_deposit(&mut ctx.svm, &ctx.pool_key, &user, 600);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("User Deposit limit exceeded"));
+    let quote = quote_deposit(&mut ctx.svm, &ctx.pool_key, &user, 400)?;
+    assert_eq!(quote.fee, bps_up(400, 10)?);
+    Ok(())
+}
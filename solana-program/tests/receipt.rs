@@ -0,0 +1,328 @@
+mod common;
+use anchor_spl::{
+    associated_token::{
+        get_associated_token_address_with_program_id,
+        spl_associated_token_account::instruction::create_associated_token_account,
+    },
+    token_2022::spl_token_2022,
+};
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chicken::{
+    actions::{bps_up, InitializePoolArgs},
+    state::{Pool, PoolMode, UserPosition},
+};
+use common::*;
+use litesvm::LiteSVM;
+use litesvm_token::spl_token;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{
+    program_pack::Pack, signature::Keypair, signer::Signer, transaction::Transaction,
+};
+
+fn transfer_receipt(
+    svm: &mut LiteSVM,
+    seller: &Keypair,
+    buyer: &Keypair,
+    receipt_mint: &Pubkey,
+) -> Result<Pubkey> {
+    let seller_receipt_ata = get_associated_token_address_with_program_id(
+        &seller.pubkey(),
+        receipt_mint,
+        &spl_token_2022::ID,
+    );
+    let buyer_receipt_ata = get_associated_token_address_with_program_id(
+        &buyer.pubkey(),
+        receipt_mint,
+        &spl_token_2022::ID,
+    );
+    let create_ata = create_associated_token_account(
+        &buyer.pubkey(),
+        &buyer.pubkey(),
+        receipt_mint,
+        &spl_token_2022::ID,
+    );
+    let transfer = spl_token_2022::instruction::transfer_checked(
+        &spl_token_2022::ID,
+        &seller_receipt_ata,
+        receipt_mint,
+        &buyer_receipt_ata,
+        &seller.pubkey(),
+        &[],
+        1,
+        0,
+    )?;
+    let tx = Transaction::new_signed_with_payer(
+        &[create_ata, transfer],
+        Some(&buyer.pubkey()),
+        &[buyer, seller],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .map_err(|e| anyhow::anyhow!("Failed to transfer receipt: {:?}", e))?;
+    Ok(buyer_receipt_ata)
+}
+
+#[test_log::test]
+fn test_receipt_holder_withdraws_position() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 0,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        position_receipts: true,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (seller, _) = setup_user(&mut ctx, deposit_amount)?;
+    let (buyer, buyer_ata) = setup_user(&mut ctx, 0)?;
+    let (user_position, receipt_mint) = deposit_with_receipt(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &seller,
+        deposit_amount,
+    )?;
+
+    let position = ctx.svm.get_account(&user_position).unwrap();
+    let position = UserPosition::deserialize(&mut &position.data[8..]).unwrap();
+    assert_eq!(position.receipt_mint, Some(receipt_mint));
+
+    let buyer_receipt_ata = transfer_receipt(&mut ctx.svm, &seller, &buyer, &receipt_mint)?;
+
+    ctx.svm.warp_to_slot(current_clock + 500);
+    let result = withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &seller);
+    assert!(result.is_err());
+
+    withdraw_with_receipt(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &ctx.mint,
+        &buyer,
+        &seller.pubkey(),
+        &user_position,
+        &receipt_mint,
+    )?;
+
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.users, 0);
+    let returned = position.deposit_amount + position.collateral_amount;
+    let buyer_ata = ctx.svm.get_account(&buyer_ata).unwrap();
+    let buyer_ata = spl_token::state::Account::unpack(&buyer_ata.data).unwrap();
+    assert_eq!(
+        buyer_ata.amount,
+        returned - bps_up(returned, pool.withdraw_fee_bps)?
+    );
+    // the burnt receipt's token account and mint are both closed
+    assert!(ctx
+        .svm
+        .get_account(&buyer_receipt_ata)
+        .map_or(true, |a| a.lamports == 0));
+    assert!(ctx
+        .svm
+        .get_account(&receipt_mint)
+        .map_or(true, |a| a.lamports == 0));
+    Ok(())
+}
+
+#[test_log::test]
+fn test_receipt_pool_requires_receipt_accounts() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 0,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        position_receipts: true,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (user, _) = setup_user(&mut ctx, 1000)?;
+    let result = deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 1000);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Position receipt accounts required"));
+    Ok(())
+}
+
+#[test_log::test]
+fn test_settle_pays_receipt_holder() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        position_receipts: true,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (seller, _) = setup_user(&mut ctx, deposit_amount)?;
+    let (buyer, buyer_ata) = setup_user(&mut ctx, 0)?;
+    let (cranker, _) = setup_user(&mut ctx, 0)?;
+    let (user_position, receipt_mint) = deposit_with_receipt(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &seller,
+        deposit_amount,
+    )?;
+    let position = ctx.svm.get_account(&user_position).unwrap();
+    let position = UserPosition::deserialize(&mut &position.data[8..]).unwrap();
+    let buyer_receipt_ata = transfer_receipt(&mut ctx.svm, &seller, &buyer, &receipt_mint)?;
+
+    // the receipt moved, so the original owner can no longer be paid
+    ctx.svm.warp_to_slot(current_clock + 1001);
+    let result = settle_receipt_position(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &ctx.mint,
+        &cranker,
+        &seller.pubkey(),
+        &seller.pubkey(),
+    );
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Invalid position receipt"));
+
+    settle_receipt_position(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &ctx.mint,
+        &cranker,
+        &seller.pubkey(),
+        &buyer.pubkey(),
+    )?;
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.users, 0);
+    assert!(ctx.svm.get_account(&user_position).is_none());
+    let returned = position.deposit_amount + position.collateral_amount;
+    let buyer_ata = ctx.svm.get_account(&buyer_ata).unwrap();
+    let buyer_ata = spl_token::state::Account::unpack(&buyer_ata.data).unwrap();
+    assert_eq!(
+        buyer_ata.amount,
+        returned - bps_up(returned, pool.withdraw_fee_bps)?
+    );
+    // the paid-out receipt is burnt and its mint closed, so it can't be sold on
+    let buyer_receipt = ctx.svm.get_account(&buyer_receipt_ata).unwrap();
+    assert_eq!(
+        u64::from_le_bytes(buyer_receipt.data[64..72].try_into().unwrap()),
+        0
+    );
+    assert!(ctx
+        .svm
+        .get_account(&receipt_mint)
+        .map_or(true, |a| a.lamports == 0));
+    Ok(())
+}
+
+#[test_log::test]
+fn test_receipt_position_top_up() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        position_receipts: true,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (user, _) = setup_user(&mut ctx, 2_000)?;
+    let (user_position, receipt_mint) =
+        deposit_with_receipt(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 1_000)?;
+    // the receipt already exists, so a top-up leaves the receipt accounts out
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 1_000)?;
+
+    let position = ctx.svm.get_account(&user_position).unwrap();
+    let position = UserPosition::deserialize(&mut &position.data[8..]).unwrap();
+    assert_eq!(position.receipt_mint, Some(receipt_mint));
+    assert_eq!(
+        position.deposit_amount + position.collateral_amount + position.deposit_fee,
+        2_000
+    );
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.users, 1);
+    Ok(())
+}
+
+#[test_log::test]
+fn test_receipt_position_redeposit_after_exit() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        position_receipts: true,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (user, _) = setup_user(&mut ctx, 2_000)?;
+    let (user_position, receipt_mint) =
+        deposit_with_receipt(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 1_000)?;
+    withdraw_with_receipt(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &ctx.mint,
+        &user,
+        &user.pubkey(),
+        &user_position,
+        &receipt_mint,
+    )?;
+
+    // the position and its receipt mint were closed on exit, so the same PDAs can be reopened
+    ctx.svm.expire_blockhash();
+    let (new_position, new_receipt_mint) =
+        deposit_with_receipt(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 500)?;
+    assert_eq!(new_position, user_position);
+    assert_eq!(new_receipt_mint, receipt_mint);
+    let user_receipt_ata = get_associated_token_address_with_program_id(
+        &user.pubkey(),
+        &receipt_mint,
+        &spl_token_2022::ID,
+    );
+    let user_receipt = ctx.svm.get_account(&user_receipt_ata).unwrap();
+    assert_eq!(
+        u64::from_le_bytes(user_receipt.data[64..72].try_into().unwrap()),
+        1
+    );
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.users, 1);
+    Ok(())
+}
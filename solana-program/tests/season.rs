@@ -0,0 +1,222 @@
+mod common;
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chicken::{
+    actions::{bps, bps_up, InitializePoolArgs, InitializeSeasonArgs},
+    state::{Pool, PoolMode, Season, SeasonEntry},
+    SEASON_WIN_POINTS,
+};
+use common::*;
+use litesvm_token::spl_token;
+use solana_sdk::{program_pack::Pack, signature::Keypair, signer::Signer};
+
+#[test_log::test]
+fn test_season_accrues_points_and_pays_prize() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let admin = Keypair::new();
+    ctx.svm.airdrop(&admin.pubkey(), 1_000_000_000).unwrap();
+    initialize_admin(&mut ctx.svm, &admin)?;
+    let season = initialize_season(
+        &mut ctx.svm,
+        &admin,
+        &ctx.mint,
+        InitializeSeasonArgs {
+            season_id: 1,
+            start_slot: current_clock,
+            end_slot: current_clock + 2000,
+            fee_share_bps: 5_000,
+        },
+    )?;
+
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 0,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, _) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, user2_ata) = setup_user(&mut ctx, deposit_amount)?;
+    deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user1,
+        deposit_amount,
+    )?;
+    deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user2,
+        deposit_amount,
+    )?;
+
+    ctx.svm.warp_to_slot(current_clock + 111);
+    withdraw_in_season(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1, &season)?;
+    ctx.svm.warp_to_slot(current_clock + 311);
+    withdraw_in_season(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user2, &season)?;
+
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    let fee = bps_up(deposit_amount, pool.deposit_fee_bps)?;
+    let collateral = bps(deposit_amount - fee, pool.collateral_bps)?;
+    let principal = deposit_amount - fee - collateral;
+    let loser_fee = bps_up(principal, pool.withdraw_fee_bps)?;
+    let winner_fee = bps_up(principal + collateral * 2, pool.withdraw_fee_bps)?;
+    let prize_pool = bps(loser_fee, 5_000)? + bps(winner_fee, 5_000)?;
+
+    let season_account = ctx.svm.get_account(&season).unwrap();
+    let season_account = Season::deserialize(&mut &season_account.data[8..]).unwrap();
+    assert_eq!(season_account.prize_pool, prize_pool);
+    let user1_points = 100;
+    let user2_points = 300 + SEASON_WIN_POINTS;
+    let user1_leader = season_account
+        .leaders
+        .iter()
+        .find(|leader| leader.player == user1.pubkey())
+        .unwrap();
+    assert_eq!(user1_leader.points, user1_points);
+    let entry = ctx
+        .svm
+        .get_account(&season_entry_key(&season, &user2.pubkey()))
+        .unwrap();
+    let entry = SeasonEntry::deserialize(&mut &entry.data[8..]).unwrap();
+    assert_eq!(entry.points, user2_points);
+    let pool_ata = ctx.svm.get_account(&ctx.pool_ata).unwrap();
+    let pool_ata = spl_token::state::Account::unpack(&pool_ata.data).unwrap();
+    assert_eq!(pool_ata.amount, pool.fee_amount);
+
+    let result = claim_season_prize(&mut ctx.svm, &season, &ctx.mint, &user2);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Season is not over"));
+
+    ctx.svm.warp_to_slot(current_clock + 2001);
+    let before = ctx.svm.get_account(&user2_ata).unwrap();
+    let before = spl_token::state::Account::unpack(&before.data).unwrap();
+    claim_season_prize(&mut ctx.svm, &season, &ctx.mint, &user2)?;
+    let after = ctx.svm.get_account(&user2_ata).unwrap();
+    let after = spl_token::state::Account::unpack(&after.data).unwrap();
+    assert_eq!(
+        after.amount - before.amount,
+        prize_pool * user2_points / (user1_points + user2_points)
+    );
+
+    ctx.svm.expire_blockhash();
+    let result = claim_season_prize(&mut ctx.svm, &season, &ctx.mint, &user2);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("No season prize to claim"));
+    Ok(())
+}
+
+#[test_log::test]
+fn test_initialize_season_requires_admin() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let admin = Keypair::new();
+    ctx.svm.airdrop(&admin.pubkey(), 1_000_000_000).unwrap();
+    initialize_admin(&mut ctx.svm, &admin)?;
+    let result = initialize_season(
+        &mut ctx.svm,
+        &ctx.creator,
+        &ctx.mint,
+        InitializeSeasonArgs {
+            season_id: 1,
+            start_slot: 0,
+            end_slot: 100,
+            fee_share_bps: 5_000,
+        },
+    );
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[test_log::test]
+fn test_season_points_need_a_stake_held_in_season() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let admin = Keypair::new();
+    ctx.svm.airdrop(&admin.pubkey(), 1_000_000_000).unwrap();
+    initialize_admin(&mut ctx.svm, &admin)?;
+    let season = initialize_season(
+        &mut ctx.svm,
+        &admin,
+        &ctx.mint,
+        InitializeSeasonArgs {
+            season_id: 1,
+            start_slot: current_clock + 100,
+            end_slot: current_clock + 2000,
+            fee_share_bps: 5_000,
+        },
+    )?;
+
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (staker, _) = setup_user(&mut ctx, 1_000_000)?;
+    let (idler, _) = setup_user(&mut ctx, 0)?;
+    let (holder, _) = setup_user(&mut ctx, 1_000_000)?;
+    for (user, amount) in [(&staker, 1_000_000), (&idler, 0), (&holder, 1_000_000)] {
+        deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, user, amount)?;
+    }
+
+    ctx.svm.warp_to_slot(current_clock + 311);
+    withdraw_in_season(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &staker, &season)?;
+    withdraw_in_season(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &idler, &season)?;
+
+    let points = |player: &Keypair| {
+        let entry = ctx
+            .svm
+            .get_account(&season_entry_key(&season, &player.pubkey()))
+            .unwrap();
+        SeasonEntry::deserialize(&mut &entry.data[8..])
+            .unwrap()
+            .points
+    };
+    // the hold before the season started does not count
+    assert_eq!(points(&staker), 211);
+    assert_eq!(points(&idler), 0);
+    Ok(())
+}
+
+#[test_log::test]
+fn test_initialize_season_rejects_fee_share_above_total() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let admin = Keypair::new();
+    ctx.svm.airdrop(&admin.pubkey(), 1_000_000_000).unwrap();
+    initialize_admin(&mut ctx.svm, &admin)?;
+    let result = initialize_season(
+        &mut ctx.svm,
+        &admin,
+        &ctx.mint,
+        InitializeSeasonArgs {
+            season_id: 1,
+            start_slot: 0,
+            end_slot: 100,
+            fee_share_bps: 10_001,
+        },
+    );
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Season fee share above 10000 bps"));
+    Ok(())
+}
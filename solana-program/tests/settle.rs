@@ -0,0 +1,130 @@
+mod common;
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chicken::{
+    actions::{bps, bps_up, InitializePoolArgs},
+    state::{Pool, PoolMode},
+    SETTLE_BOUNTY_BPS,
+};
+use common::*;
+use litesvm_token::spl_token;
+use solana_sdk::{program_pack::Pack, signer::Signer};
+
+#[test_log::test]
+fn test_settle_abandoned_positions() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 0,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, _) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, user2_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (user3, user3_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (cranker, cranker_ata) = setup_user(&mut ctx, 0)?;
+    for user in [&user1, &user2, &user3] {
+        deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, user, deposit_amount)?;
+    }
+
+    ctx.svm.warp_to_slot(current_clock + 500);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1)?;
+
+    // user2 and user3 never come back
+    ctx.svm.warp_to_slot(current_clock + 1001);
+    let pool_before = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool_before = Pool::deserialize(&mut &pool_before.data[8..]).unwrap();
+    settle(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &ctx.mint,
+        &cranker,
+        Some(cranker_ata),
+        &[user2.pubkey(), user3.pubkey()],
+    )?;
+
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.users, 0);
+    assert_eq!(pool.collateral_amount, 0);
+    for user in [&user2, &user3] {
+        let position = ctx
+            .svm
+            .get_account(&user_position_key(&ctx.pool_key, &user.pubkey()));
+        assert!(position.map_or(true, |a| a.data.is_empty()));
+    }
+
+    let fee = bps_up(deposit_amount, pool.deposit_fee_bps)?;
+    let collateral = bps(deposit_amount - fee, pool.collateral_bps)?;
+    let position_amount = deposit_amount - fee - collateral;
+    let user2_return = position_amount - bps_up(position_amount, pool.withdraw_fee_bps)?;
+    let user3_gross = position_amount + pool_before.collateral_amount;
+    let user3_return = user3_gross - bps_up(user3_gross, pool.withdraw_fee_bps)?;
+
+    let user2_ata = ctx.svm.get_account(&user2_ata).unwrap();
+    let user2_ata = spl_token::state::Account::unpack(&user2_ata.data).unwrap();
+    let user3_ata = ctx.svm.get_account(&user3_ata).unwrap();
+    let user3_ata = spl_token::state::Account::unpack(&user3_ata.data).unwrap();
+    assert_eq!(user2_ata.amount, user2_return);
+    assert_eq!(user3_ata.amount, user3_return);
+
+    let bounty = bps(
+        (position_amount - user2_return) + (user3_gross - user3_return),
+        SETTLE_BOUNTY_BPS,
+    )?;
+    let cranker_ata = ctx.svm.get_account(&cranker_ata).unwrap();
+    let cranker_ata = spl_token::state::Account::unpack(&cranker_ata.data).unwrap();
+    assert_eq!(cranker_ata.amount, bounty);
+
+    let pool_ata = ctx.svm.get_account(&ctx.pool_ata).unwrap();
+    let pool_ata = spl_token::state::Account::unpack(&pool_ata.data).unwrap();
+    assert_eq!(pool_ata.amount, pool.fee_amount);
+    Ok(())
+}
+
+#[test_log::test]
+fn test_settle_pool_not_ended() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 0,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (user, _) = setup_user(&mut ctx, 1000)?;
+    let (cranker, _) = setup_user(&mut ctx, 0)?;
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 1000)?;
+
+    let result = settle(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &ctx.mint,
+        &cranker,
+        None,
+        &[user.pubkey()],
+    );
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Pool is not Ended"));
+    Ok(())
+}
@@ -0,0 +1,259 @@
+mod common;
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chicken::{
+    actions::{bps, bps_up, InitializePoolArgs},
+    state::{Pool, PoolMode, PoolState},
+};
+use common::*;
+use litesvm_token::spl_token;
+use solana_sdk::{program_pack::Pack, signature::Keypair, signer::Signer};
+
+#[test_log::test]
+fn test_shares_last_holder_takes_collateral() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 0,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        share_tokens: true,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.share_mint, Some(share_mint_key(&ctx.pool_key)));
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, user1_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, user2_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let user1_shares = deposit_shares(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user1,
+        deposit_amount,
+    )?;
+    let user2_shares = deposit_shares(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user2,
+        deposit_amount,
+    )?;
+
+    let fee = bps_up(deposit_amount, pool.deposit_fee_bps)?;
+    let collateral = bps(deposit_amount - fee, pool.collateral_bps)?;
+    let principal = deposit_amount - fee - collateral;
+    let user1_shares = ctx.svm.get_account(&user1_shares).unwrap();
+    let user1_shares = spl_token::state::Account::unpack(&user1_shares.data).unwrap();
+    let user2_shares = ctx.svm.get_account(&user2_shares).unwrap();
+    let user2_shares = spl_token::state::Account::unpack(&user2_shares.data).unwrap();
+    assert_eq!(user1_shares.amount, principal);
+    assert_eq!(user2_shares.amount, principal);
+
+    ctx.svm.warp_to_slot(current_clock + 500);
+    withdraw_shares(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user1,
+        user1_shares.amount,
+    )?;
+    withdraw_shares(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user2,
+        user2_shares.amount,
+    )?;
+
+    let user1_ata = ctx.svm.get_account(&user1_ata).unwrap();
+    let user1_ata = spl_token::state::Account::unpack(&user1_ata.data).unwrap();
+    let user2_ata = ctx.svm.get_account(&user2_ata).unwrap();
+    let user2_ata = spl_token::state::Account::unpack(&user2_ata.data).unwrap();
+    assert_eq!(
+        user1_ata.amount,
+        principal - bps_up(principal, pool.withdraw_fee_bps)?
+    );
+    let last_out = principal + collateral * 2;
+    assert_eq!(
+        user2_ata.amount,
+        last_out - bps_up(last_out, pool.withdraw_fee_bps)?
+    );
+
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.collateral_amount, 0);
+    let pool_ata = ctx.svm.get_account(&ctx.pool_ata).unwrap();
+    let pool_ata = spl_token::state::Account::unpack(&pool_ata.data).unwrap();
+    assert_eq!(pool_ata.amount, pool.fee_amount);
+    Ok(())
+}
+
+#[test_log::test]
+fn test_shares_pool_rejects_position_deposit() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 0,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        share_tokens: true,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (user, _) = setup_user(&mut ctx, 1000)?;
+    let result = deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 1000);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Pool uses share tokens"));
+    Ok(())
+}
+
+#[test_log::test]
+fn test_deposit_shares_respects_fee_limit() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        share_tokens: true,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (user, _) = setup_user(&mut ctx, 1000)?;
+    let result =
+        deposit_shares_with_limits(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 1000, 5, 500);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Pool fees exceed the accepted maximum"));
+    deposit_shares_with_limits(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 1000, 10, 500)?;
+    Ok(())
+}
+
+#[test_log::test]
+fn test_deposit_shares_books_like_a_deposit() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        max_deposit: Some(1000),
+        share_tokens: true,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (user, _) = setup_user(&mut ctx, 3000)?;
+    // the whole deposit goes to the fee, so there is nothing to mint shares for
+    let result = deposit_shares(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 1);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Invalid share amount"));
+    let result = deposit_shares(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 1001);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("User Deposit limit exceeded"));
+
+    // the limit bounds each deposit, since shares can change hands
+    deposit_shares(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 1000)?;
+    ctx.svm.expire_blockhash();
+    deposit_shares(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 1000)?;
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    let fee = bps_up(1000, pool.deposit_fee_bps)?;
+    let collateral = bps(1000 - fee, pool.collateral_bps)?;
+    assert_eq!(pool.users, 0);
+    assert_eq!(pool.collateral_amount, collateral * 2);
+    assert_eq!(pool.open_collateral, collateral * 2);
+    assert_eq!(pool.total_principal, (1000 - fee - collateral) * 2);
+    Ok(())
+}
+
+#[test_log::test]
+fn test_remove_share_pool_waits_for_share_holders() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let admin = Keypair::new();
+    ctx.svm.airdrop(&admin.pubkey(), 1_000_000_000).unwrap();
+    initialize_admin(&mut ctx.svm, &admin)?;
+    initialize_treasury(&mut ctx.svm, &admin, &ctx.mint, 0)?;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        share_tokens: true,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+    let share_mint = share_mint_key(&ctx.pool_key);
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user, _) = setup_user(&mut ctx, deposit_amount)?;
+    let user_shares = deposit_shares(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user,
+        deposit_amount,
+    )?;
+
+    // share holders never show up in `users`, the pool is still owed to them
+    ctx.svm.warp_to_slot(current_clock + 1001);
+    sweep_fees_to_treasury(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user)?;
+    let result = remove_pool(&mut ctx.svm, &ctx.pool_key, &ctx.creator, Some(share_mint));
+    assert!(result.is_err());
+
+    let user_shares = ctx.svm.get_account(&user_shares).unwrap();
+    let user_shares = spl_token::state::Account::unpack(&user_shares.data).unwrap();
+    withdraw_shares(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user,
+        user_shares.amount,
+    )?;
+    ctx.svm.expire_blockhash();
+    sweep_fees_to_treasury(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user)?;
+    let result = remove_pool(&mut ctx.svm, &ctx.pool_key, &ctx.creator, None);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Share mint required"));
+    remove_pool(&mut ctx.svm, &ctx.pool_key, &ctx.creator, Some(share_mint))?;
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.state, PoolState::Removed);
+    Ok(())
+}
@@ -0,0 +1,213 @@
+mod common;
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chicken::{
+    actions::{bps, bps_up, InitializePoolArgs, SuddenDeathArgs},
+    state::{Pool, PoolMode, UserPosition},
+    SUDDEN_DEATH_BUCKETS, SUDDEN_DEATH_REVEAL_TIMEOUT,
+};
+use common::*;
+use solana_program::{
+    hash::{hashv, Hash},
+    slot_hashes::SlotHashes,
+};
+use solana_sdk::signer::Signer;
+
+const SECRET: [u8; 32] = [7; 32];
+const SALT: [u8; 32] = [9; 32];
+
+fn sudden_death_args(current_clock: u64) -> InitializePoolArgs {
+    InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        sudden_death: Some(SuddenDeathArgs {
+            commitment: hashv(&[&SECRET, &SALT]).to_bytes(),
+            earliest_end: current_clock + 500,
+            bond: 1_000_000,
+        }),
+        ..Default::default()
+    }
+}
+
+#[test_log::test]
+fn test_sudden_death_reveal_decides_winners() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    setup_pool(&mut ctx, &sudden_death_args(current_clock))?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, user1_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, user2_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (user3, user3_ata) = setup_user(&mut ctx, deposit_amount)?;
+    for user in [&user1, &user2, &user3] {
+        deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, user, deposit_amount)?;
+    }
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    let fee = bps_up(deposit_amount, pool.deposit_fee_bps)?;
+    let collateral = bps(deposit_amount - fee, pool.collateral_bps)?;
+    let principal = deposit_amount - fee - collateral;
+    let payout = |amount: u64| amount - bps_up(amount, pool.withdraw_fee_bps).unwrap();
+
+    // leaving before the window is an ordinary loss
+    ctx.svm.warp_to_slot(current_clock + 100);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1)?;
+
+    // the latest possible end, so this exit is always a survivor
+    ctx.svm.warp_to_slot(current_clock + 1000);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user2)?;
+    assert_eq!(balance(&ctx.svm, &user1_ata), payout(principal));
+    assert_eq!(balance(&ctx.svm, &user2_ata), payout(principal));
+    let user2_position = user_position_key(&ctx.pool_key, &user2.pubkey());
+    let position = ctx.svm.get_account(&user2_position).unwrap();
+    let position = UserPosition::deserialize(&mut &position.data[8..]).unwrap();
+    assert_eq!(position.exit_slot, current_clock + 1000);
+
+    ctx.svm.warp_to_slot(current_clock + 1001);
+    set_slot_hash(&mut ctx.svm, current_clock + 1000, [1; 32]);
+    let result = withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user3);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Pool end has not been revealed"));
+    let result = reveal_end(&mut ctx.svm, &ctx.pool_key, &ctx.creator, SECRET, [0; 32]);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Reveal does not match commitment"));
+    reveal_end(&mut ctx.svm, &ctx.pool_key, &ctx.creator, SECRET, SALT)?;
+    ctx.svm.expire_blockhash();
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    let revealed_end = pool.sudden_death.unwrap().revealed_end.unwrap();
+    assert!(revealed_end >= current_clock + 500 && revealed_end <= current_clock + 1000);
+
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user2)?;
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user3)?;
+    let pot = collateral * 3;
+    let user2_share = pot * collateral / (collateral * 2);
+    assert_eq!(
+        balance(&ctx.svm, &user2_ata),
+        payout(principal) + payout(user2_share)
+    );
+    assert_eq!(
+        balance(&ctx.svm, &user3_ata),
+        payout(principal + pot - user2_share)
+    );
+    Ok(())
+}
+
+#[test_log::test]
+fn test_sudden_death_timeout_slashes_bond() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    setup_pool(&mut ctx, &sudden_death_args(current_clock))?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (winner, _) = setup_user(&mut ctx, 1000)?;
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &winner, 1000)?;
+
+    ctx.svm.warp_to_slot(current_clock + 500);
+    let (user, _) = setup_user(&mut ctx, 1000)?;
+    let result = deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, 1000);
+    assert!(result.unwrap_err().to_string().contains("Pool has Ended"));
+
+    ctx.svm.warp_to_slot(current_clock + 1001);
+    let result = reveal_end(&mut ctx.svm, &ctx.pool_key, &user, [0; 32], [0; 32]);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Reveal window has not passed"));
+
+    // whoever settles the end after the timeout gets nothing, the bond goes to the winners
+    ctx.svm
+        .warp_to_slot(current_clock + 1001 + SUDDEN_DEATH_REVEAL_TIMEOUT);
+    set_slot_hash(&mut ctx.svm, current_clock + 1000, [1; 32]);
+    let before = ctx.svm.get_account(&user.pubkey()).unwrap().lamports;
+    reveal_end(&mut ctx.svm, &ctx.pool_key, &user, [0; 32], [0; 32])?;
+    let after = ctx.svm.get_account(&user.pubkey()).unwrap().lamports;
+    assert_eq!(before - after, 5_000);
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.sudden_death.unwrap().bond, 1_000_000);
+
+    let position = user_position_key(&ctx.pool_key, &winner.pubkey());
+    let rent = ctx.svm.get_account(&position).unwrap().lamports;
+    let before = ctx.svm.get_account(&winner.pubkey()).unwrap().lamports;
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &winner)?;
+    let after = ctx.svm.get_account(&winner.pubkey()).unwrap().lamports;
+    assert_eq!(after - before, rent + 1_000_000 - 5_000);
+    Ok(())
+}
+
+#[test_log::test]
+fn test_sudden_death_end_comes_from_the_end_slot_hash() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    setup_pool(&mut ctx, &sudden_death_args(current_clock))?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, user1_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, user2_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (user3, user3_ata) = setup_user(&mut ctx, deposit_amount)?;
+    for user in [&user1, &user2, &user3] {
+        deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, user, deposit_amount)?;
+    }
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    let fee = bps_up(deposit_amount, pool.deposit_fee_bps)?;
+    let collateral = bps(deposit_amount - fee, pool.collateral_bps)?;
+    let principal = deposit_amount - fee - collateral;
+    let payout = |amount: u64| amount - bps_up(amount, pool.withdraw_fee_bps).unwrap();
+
+    // the first window exit sits in the first bucket, the second at the latest possible end
+    ctx.svm.warp_to_slot(current_clock + 500);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1)?;
+    ctx.svm.warp_to_slot(current_clock + 1000);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user2)?;
+
+    // a newer block must not move the end, only the block at `end_time` counts
+    ctx.svm.warp_to_slot(current_clock + 1010);
+    let end_hash = [3; 32];
+    ctx.svm.set_sysvar(&SlotHashes::new(&[
+        (current_clock + 1009, Hash::new_from_array([2; 32])),
+        (current_clock + 1000, Hash::new_from_array(end_hash)),
+        (current_clock + 999, Hash::new_from_array([4; 32])),
+    ]));
+    reveal_end(&mut ctx.svm, &ctx.pool_key, &ctx.creator, SECRET, SALT)?;
+    ctx.svm.expire_blockhash();
+    let bucket_len = 501u64.div_ceil(SUDDEN_DEATH_BUCKETS as u64);
+    let mixed = hashv(&[&SECRET, &end_hash]).to_bytes();
+    let end_bucket =
+        u64::from_le_bytes(mixed[..8].try_into().unwrap()) % 501u64.div_ceil(bucket_len);
+    assert!(end_bucket > 0);
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    let sudden_death = pool.sudden_death.unwrap();
+    assert_eq!(
+        sudden_death.revealed_end,
+        Some(current_clock + 500 + end_bucket * bucket_len)
+    );
+    assert_eq!(sudden_death.winning_collateral, collateral * 2);
+
+    for user in [&user1, &user2, &user3] {
+        withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, user)?;
+    }
+    let pot = collateral * 3;
+    let user2_share = pot / 2;
+    assert_eq!(balance(&ctx.svm, &user1_ata), payout(principal));
+    assert_eq!(
+        balance(&ctx.svm, &user2_ata),
+        payout(principal) + payout(user2_share)
+    );
+    assert_eq!(
+        balance(&ctx.svm, &user3_ata),
+        payout(principal + pot - user2_share)
+    );
+    Ok(())
+}
@@ -0,0 +1,128 @@
+mod common;
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chicken::{
+    actions::{bps, bps_up, InitializePoolArgs},
+    state::{Pool, PoolMode, UserPosition},
+};
+use common::*;
+use litesvm_token::spl_token;
+use solana_sdk::{program_pack::Pack, signer::Signer};
+
+#[test_log::test]
+fn test_survival_eliminated_collateral_goes_to_survivors() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::Survival { interval: 100 },
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, user1_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, user2_ata) = setup_user(&mut ctx, deposit_amount)?;
+    let (user3, user3_ata) = setup_user(&mut ctx, deposit_amount)?;
+    for user in [&user1, &user2, &user3] {
+        deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, user, deposit_amount)?;
+    }
+
+    ctx.svm.warp_to_slot(current_clock + 100);
+    check_in(&mut ctx.svm, &ctx.pool_key, &user1)?;
+    check_in(&mut ctx.svm, &ctx.pool_key, &user2)?;
+    let result = eliminate(&mut ctx.svm, &ctx.pool_key, &user1, &user3.pubkey());
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Position has not missed a check-in"));
+
+    ctx.svm.warp_to_slot(current_clock + 150);
+    let result = check_in(&mut ctx.svm, &ctx.pool_key, &user3);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Check-in interval missed"));
+    eliminate(&mut ctx.svm, &ctx.pool_key, &user1, &user3.pubkey())?;
+    let position = ctx
+        .svm
+        .get_account(&user_position_key(&ctx.pool_key, &user3.pubkey()))
+        .unwrap();
+    let position = UserPosition::deserialize(&mut &position.data[8..]).unwrap();
+    assert!(position.eliminated);
+
+    for slot in (200..1000).step_by(100) {
+        ctx.svm.warp_to_slot(current_clock + slot);
+        ctx.svm.expire_blockhash();
+        check_in(&mut ctx.svm, &ctx.pool_key, &user1)?;
+        check_in(&mut ctx.svm, &ctx.pool_key, &user2)?;
+    }
+
+    ctx.svm.warp_to_slot(current_clock + 1001);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user3)?;
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1)?;
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user2)?;
+
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    let fee = bps_up(deposit_amount, pool.deposit_fee_bps)?;
+    let collateral = bps(deposit_amount - fee, pool.collateral_bps)?;
+    let principal = deposit_amount - fee - collateral;
+    let pot = collateral * 3;
+    let user1_share = pot * collateral / (collateral * 2);
+    let payout = |amount: u64| amount - bps_up(amount, pool.withdraw_fee_bps).unwrap();
+    let balance = |ata| {
+        let account = ctx.svm.get_account(ata).unwrap();
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    };
+    assert_eq!(balance(&user3_ata), payout(principal));
+    assert_eq!(balance(&user1_ata), payout(principal + user1_share));
+    assert_eq!(balance(&user2_ata), payout(principal + pot - user1_share));
+    assert_eq!(pool.collateral_amount, 0);
+    assert_eq!(pool.open_collateral, 0);
+    Ok(())
+}
+
+#[test_log::test]
+fn test_survival_top_up_cannot_cover_missed_check_in() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::Survival { interval: 100 },
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, _) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, _) = setup_user(&mut ctx, deposit_amount * 2)?;
+    for user in [&user1, &user2] {
+        deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, user, deposit_amount)?;
+    }
+
+    ctx.svm.warp_to_slot(current_clock + 150);
+    let result = deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user2,
+        deposit_amount,
+    );
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Check-in interval missed"));
+    eliminate(&mut ctx.svm, &ctx.pool_key, &user1, &user2.pubkey())?;
+    Ok(())
+}
@@ -0,0 +1,147 @@
+mod common;
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chicken::{
+    actions::InitializePoolArgs,
+    state::{Pool, PoolMode},
+};
+use common::*;
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+#[test_log::test]
+fn test_sweep_dust_after_last_exit() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let admin = Keypair::new();
+    ctx.svm.airdrop(&admin.pubkey(), 1_000_000_000).unwrap();
+    initialize_admin(&mut ctx.svm, &admin)?;
+    let treasury_ata = initialize_treasury(&mut ctx.svm, &admin, &ctx.mint, 0)?;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    // odd amounts so every fee rounds
+    let (user1, _) = setup_user(&mut ctx, 1_234_567)?;
+    let (user2, _) = setup_user(&mut ctx, 7_654_321)?;
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user1, 1_234_567)?;
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user2, 7_654_321)?;
+    let result = sweep_dust(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &admin, None);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Pool still has users"));
+
+    ctx.svm.warp_to_slot(current_clock + 500);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1)?;
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user2)?;
+    let result = sweep_dust(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &admin, None);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Pool is not Ended"));
+
+    ctx.svm.warp_to_slot(current_clock + 1001);
+    // anything sent straight to the vault is not owed to anyone
+    mint_to(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.mint_authority,
+        &ctx.pool_ata,
+        777,
+    )?;
+    let outsider = Keypair::new();
+    ctx.svm.airdrop(&outsider.pubkey(), 1_000_000_000).unwrap();
+    let result = sweep_dust(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &outsider, None);
+    assert!(result.unwrap_err().to_string().contains("Unauthorized"));
+    ctx.svm.expire_blockhash();
+    sweep_dust(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &admin, None)?;
+
+    assert_eq!(balance(&ctx.svm, &treasury_ata), 777);
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(balance(&ctx.svm, &ctx.pool_ata), pool.fee_amount);
+    assert_eq!(pool.total_principal, 0);
+    assert_eq!(pool.collateral_amount, 0);
+
+    ctx.svm.expire_blockhash();
+    let result = sweep_dust(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &admin, None);
+    assert!(result.unwrap_err().to_string().contains("Nothing to sweep"));
+    Ok(())
+}
+
+#[test_log::test]
+fn test_sweep_dust_waits_for_share_holders() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let admin = Keypair::new();
+    ctx.svm.airdrop(&admin.pubkey(), 1_000_000_000).unwrap();
+    initialize_admin(&mut ctx.svm, &admin)?;
+    let treasury_ata = initialize_treasury(&mut ctx.svm, &admin, &ctx.mint, 0)?;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        share_tokens: true,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+    let share_mint = share_mint_key(&ctx.pool_key);
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user, _) = setup_user(&mut ctx, deposit_amount)?;
+    let user_shares = deposit_shares(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user,
+        deposit_amount,
+    )?;
+
+    // share holders never show up in `users`, the vault is still owed to them
+    ctx.svm.warp_to_slot(current_clock + 1001);
+    let result = sweep_dust(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &ctx.mint,
+        &admin,
+        Some(share_mint),
+    );
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Pool still has users"));
+
+    let shares = balance(&ctx.svm, &user_shares);
+    withdraw_shares(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &user, shares)?;
+    mint_to(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.mint_authority,
+        &ctx.pool_ata,
+        777,
+    )?;
+    let result = sweep_dust(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &admin, None);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Share mint required"));
+    sweep_dust(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &ctx.mint,
+        &admin,
+        Some(share_mint),
+    )?;
+    assert_eq!(balance(&ctx.svm, &treasury_ata), 777);
+    Ok(())
+}
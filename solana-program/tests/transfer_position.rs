@@ -0,0 +1,235 @@
+mod common;
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chicken::{
+    actions::{InitializePoolArgs, SuddenDeathArgs},
+    state::{ExitCondition, PlayerProfile, Pool, PoolMode, UserPosition},
+};
+use common::*;
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+#[test_log::test]
+fn test_transfer_position_merges_into_existing() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 0,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        transferable: true,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (sender, _) = setup_user(&mut ctx, 1_000_000)?;
+    let sender_position_key = deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &sender, 1_000_000)?;
+    ctx.svm.warp_to_slot(current_clock + 111);
+    let (receiver, _) = setup_user(&mut ctx, 3_000_000)?;
+    let receiver_position_key =
+        deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &receiver, 3_000_000)?;
+
+    let sender_position = ctx.svm.get_account(&sender_position_key).unwrap();
+    let sender_position = UserPosition::deserialize(&mut &sender_position.data[8..]).unwrap();
+    let receiver_before = ctx.svm.get_account(&receiver_position_key).unwrap();
+    let receiver_before = UserPosition::deserialize(&mut &receiver_before.data[8..]).unwrap();
+
+    transfer_position(&mut ctx.svm, &ctx.pool_key, &sender, &receiver.pubkey())?;
+
+    let sender_account = ctx.svm.get_account(&sender_position_key);
+    assert!(sender_account.map_or(true, |a| a.data.is_empty()));
+    let receiver_after = ctx.svm.get_account(&receiver_position_key).unwrap();
+    let receiver_after = UserPosition::deserialize(&mut &receiver_after.data[8..]).unwrap();
+    assert_eq!(receiver_after.owner, receiver.pubkey());
+    assert_eq!(
+        receiver_after.deposit_amount,
+        sender_position.deposit_amount + receiver_before.deposit_amount
+    );
+    assert_eq!(
+        receiver_after.collateral_amount,
+        sender_position.collateral_amount + receiver_before.collateral_amount
+    );
+    let weighted_time = (sender_position.deposit_time as u128
+        * sender_position.deposit_amount as u128
+        + receiver_before.deposit_time as u128 * receiver_before.deposit_amount as u128)
+        / (sender_position.deposit_amount as u128 + receiver_before.deposit_amount as u128);
+    assert_eq!(receiver_after.deposit_time, weighted_time as u64);
+
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let pool = Pool::deserialize(&mut &pool.data[8..]).unwrap();
+    assert_eq!(pool.users, 1);
+    Ok(())
+}
+
+#[test_log::test]
+fn test_settle_transferred_position_to_new_player() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        transferable: true,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (sender, _) = setup_user(&mut ctx, 1_000_000)?;
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &sender, 1_000_000)?;
+    // the receiver never deposited, so the transfer has to open its profile
+    let (receiver, receiver_ata) = setup_user(&mut ctx, 0)?;
+    transfer_position_with_payer(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &sender,
+        &receiver,
+        &receiver.pubkey(),
+    )?;
+    let profile = ctx
+        .svm
+        .get_account(&player_profile_key(&receiver.pubkey()))
+        .unwrap();
+    let profile = PlayerProfile::deserialize(&mut &profile.data[8..]).unwrap();
+    assert_eq!(profile.owner, receiver.pubkey());
+
+    ctx.svm.warp_to_slot(current_clock + 1001);
+    let (cranker, _) = setup_user(&mut ctx, 0)?;
+    settle(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &ctx.mint,
+        &cranker,
+        None,
+        &[receiver.pubkey()],
+    )?;
+    assert!(balance(&ctx.svm, &receiver_ata) > 0);
+    Ok(())
+}
+
+#[test_log::test]
+fn test_transfer_position_not_transferable() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 0,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (sender, _) = setup_user(&mut ctx, 1000)?;
+    let (receiver, _) = setup_user(&mut ctx, 0)?;
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &sender, 1000)?;
+
+    let result = transfer_position(&mut ctx.svm, &ctx.pool_key, &sender, &receiver.pubkey());
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Position is not transferable"));
+    Ok(())
+}
+
+#[test_log::test]
+fn test_transfer_position_closes_exit_order_and_refunds_payer() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        transferable: true,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (sender, _) = setup_user(&mut ctx, 1000)?;
+    let (receiver, _) = setup_user(&mut ctx, 0)?;
+    let sponsor = Keypair::new();
+    ctx.svm.airdrop(&sponsor.pubkey(), 1_000_000_000).unwrap();
+    let sender_position = deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &sender, 1000)?;
+    let exit_order = place_exit_order(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &sender,
+        ExitCondition::UsersAtMost(0),
+        0,
+    )?;
+
+    // the sponsor pays for the receiver's position, so its rent goes back to the sponsor
+    transfer_position_with_payer(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &sender,
+        &sponsor,
+        &receiver.pubkey(),
+    )?;
+    let exit_order = ctx.svm.get_account(&exit_order);
+    assert!(exit_order.map_or(true, |a| a.data.is_empty()));
+    let sender_account = ctx.svm.get_account(&sender_position);
+    assert!(sender_account.map_or(true, |a| a.data.is_empty()));
+
+    let receiver_position = user_position_key(&ctx.pool_key, &receiver.pubkey());
+    let rent = ctx.svm.get_account(&receiver_position).unwrap().lamports;
+    let sponsor_before = ctx.svm.get_balance(&sponsor.pubkey()).unwrap();
+    transfer_position(&mut ctx.svm, &ctx.pool_key, &receiver, &sender.pubkey())?;
+    assert_eq!(
+        ctx.svm.get_balance(&sponsor.pubkey()).unwrap(),
+        sponsor_before + rent
+    );
+    Ok(())
+}
+
+#[test_log::test]
+fn test_transfer_position_rejects_exited_target() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        transferable: true,
+        sudden_death: Some(SuddenDeathArgs {
+            commitment: [0; 32],
+            earliest_end: current_clock + 500,
+            bond: 0,
+        }),
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let (sender, _) = setup_user(&mut ctx, 1000)?;
+    let (receiver, _) = setup_user(&mut ctx, 1000)?;
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &sender, 1000)?;
+    deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, &receiver, 1000)?;
+
+    // an exit inside the sudden-death window keeps the position open until the reveal
+    ctx.svm.warp_to_slot(current_clock + 600);
+    withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &receiver)?;
+    let result = transfer_position(&mut ctx.svm, &ctx.pool_key, &sender, &receiver.pubkey());
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Position is not transferable"));
+    Ok(())
+}
@@ -0,0 +1,133 @@
+mod common;
+use anyhow::Result;
+use borsh::BorshDeserialize;
+use chicken::{
+    actions::InitializePoolArgs,
+    state::{Pool, PoolMode, Treasury},
+};
+use common::*;
+use litesvm_token::spl_token;
+use solana_program::clock::Clock;
+use solana_sdk::{program_pack::Pack, signature::Keypair, signer::Signer};
+
+#[test_log::test]
+fn test_treasury_collects_fees_and_limits_spending() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<Clock>().slot;
+    let admin = Keypair::new();
+    ctx.svm.airdrop(&admin.pubkey(), 1_000_000_000).unwrap();
+    initialize_admin(&mut ctx.svm, &admin)?;
+    let treasury_ata = initialize_treasury(&mut ctx.svm, &admin, &ctx.mint, 1_000)?;
+    let recipient = Keypair::new();
+    let recipient_ata = setup_ata(&mut ctx.svm, &ctx.mint, &recipient)?;
+
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        collateral_bps: 500,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, _) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, _) = setup_user(&mut ctx, deposit_amount)?;
+    for user in [&user1, &user2] {
+        deposit(&mut ctx.svm, &ctx.mint, &ctx.pool_key, user, deposit_amount)?;
+    }
+    let result = sweep_fees_to_treasury(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Pool is not Ended"));
+
+    ctx.svm.warp_to_slot(current_clock + 1001);
+    for user in [&user1, &user2] {
+        withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, user)?;
+    }
+    let pool = ctx.svm.get_account(&ctx.pool_key).unwrap();
+    let fees = Pool::deserialize(&mut &pool.data[8..]).unwrap().fee_amount;
+    sweep_fees_to_treasury(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1)?;
+    let balance = |svm: &litesvm::LiteSVM, ata| {
+        let account = svm.get_account(ata).unwrap();
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    };
+    assert_eq!(balance(&ctx.svm, &treasury_ata), fees);
+    let treasury = ctx.svm.get_account(&treasury_key(&ctx.mint)).unwrap();
+    let treasury = Treasury::deserialize(&mut &treasury.data[8..]).unwrap();
+    assert_eq!(treasury.total_collected, fees);
+    let result = sweep_fees_to_treasury(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user2);
+    assert!(result.unwrap_err().to_string().contains("Nothing to sweep"));
+
+    let result = withdraw_from_treasury(&mut ctx.svm, &user1, &ctx.mint, &recipient_ata, 100);
+    assert!(result.unwrap_err().to_string().contains("Unauthorized"));
+    withdraw_from_treasury(&mut ctx.svm, &admin, &ctx.mint, &recipient_ata, 600)?;
+    ctx.svm.expire_blockhash();
+    let result = withdraw_from_treasury(&mut ctx.svm, &admin, &ctx.mint, &recipient_ata, 600);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Treasury epoch limit exceeded"));
+
+    // the limit resets with the epoch
+    let mut clock = ctx.svm.get_sysvar::<Clock>();
+    clock.epoch += 1;
+    ctx.svm.set_sysvar(&clock);
+    ctx.svm.expire_blockhash();
+    withdraw_from_treasury(&mut ctx.svm, &admin, &ctx.mint, &recipient_ata, 600)?;
+    assert_eq!(balance(&ctx.svm, &recipient_ata), 1_200);
+
+    set_treasury_limit(&mut ctx.svm, &admin, &ctx.mint, 0)?;
+    let mut clock = ctx.svm.get_sysvar::<Clock>();
+    clock.epoch += 1;
+    ctx.svm.set_sysvar(&clock);
+    let result = withdraw_from_treasury(&mut ctx.svm, &admin, &ctx.mint, &recipient_ata, 1);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Treasury epoch limit exceeded"));
+    Ok(())
+}
+
+#[test_log::test]
+fn test_raised_treasury_limit_waits_for_next_epoch() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let admin = Keypair::new();
+    ctx.svm.airdrop(&admin.pubkey(), 1_000_000_000).unwrap();
+    initialize_admin(&mut ctx.svm, &admin)?;
+    let treasury_ata = initialize_treasury(&mut ctx.svm, &admin, &ctx.mint, 1_000)?;
+    mint_to(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.mint_authority,
+        &treasury_ata,
+        10_000,
+    )?;
+    let recipient = Keypair::new();
+    let recipient_ata = setup_ata(&mut ctx.svm, &ctx.mint, &recipient)?;
+
+    withdraw_from_treasury(&mut ctx.svm, &admin, &ctx.mint, &recipient_ata, 1_000)?;
+    // raising the limit mid-epoch does not open up more spending right away
+    set_treasury_limit(&mut ctx.svm, &admin, &ctx.mint, 5_000)?;
+    let result = withdraw_from_treasury(&mut ctx.svm, &admin, &ctx.mint, &recipient_ata, 1_000);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Treasury epoch limit exceeded"));
+    let treasury = ctx.svm.get_account(&treasury_key(&ctx.mint)).unwrap();
+    let treasury = Treasury::deserialize(&mut &treasury.data[8..]).unwrap();
+    assert_eq!(treasury.budget.limit, 1_000);
+    assert_eq!(treasury.budget.pending_limit, Some(5_000));
+
+    let mut clock = ctx.svm.get_sysvar::<Clock>();
+    clock.epoch += 1;
+    ctx.svm.set_sysvar(&clock);
+    ctx.svm.expire_blockhash();
+    withdraw_from_treasury(&mut ctx.svm, &admin, &ctx.mint, &recipient_ata, 5_000)?;
+    assert_eq!(balance(&ctx.svm, &recipient_ata), 6_000);
+    Ok(())
+}
@@ -2,8 +2,8 @@ mod common;
 use anyhow::Result;
 use borsh::BorshDeserialize;
 use chicken::{
-    actions::{bps, InitializePoolArgs},
-    state::{Pool, PoolMode, UserPosition},
+    actions::{bps, bps_up, InitializePoolArgs},
+    state::{PayoutCurve, Pool, PoolMode, UserPosition},
 };
 use common::*;
 use litesvm_token::spl_token;
@@ -22,6 +22,7 @@ fn test_withdraw_single_user_last_out_winner() -> Result<()> {
         collateral_bps: 500, // 5%
         max_deposit: None,
         total_deposit_limit: None,
+        ..Default::default()
     };
     setup_pool(&mut ctx, &args)?;
 
@@ -43,10 +44,10 @@ fn test_withdraw_single_user_last_out_winner() -> Result<()> {
     let user_position = ctx.svm.get_account(&user_position_key).unwrap();
     let user_position = UserPosition::deserialize(&mut &user_position.data[8..]).unwrap();
 
-    let amount_rerutned =
-        user_position.deposit_amount - (bps(user_position.deposit_amount, pool.withdraw_fee_bps)?);
-    let fees = bps(user_position.deposit_amount, pool.withdraw_fee_bps)?
-        + bps(user_position.deposit_amount, pool.deposit_fee_bps)?;
+    let amount_rerutned = user_position.deposit_amount
+        - (bps_up(user_position.deposit_amount, pool.withdraw_fee_bps)?);
+    let fees = bps_up(user_position.deposit_amount, pool.withdraw_fee_bps)?
+        + bps_up(user_position.deposit_amount, pool.deposit_fee_bps)?;
     // Withdraw phase
     ctx.svm.warp_to_slot(current_clock + 900);
     withdraw(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user)?;
@@ -79,6 +80,7 @@ fn test_withdraw_multi_user_first_out_loser() -> Result<()> {
         collateral_bps: 500, // 5%
         max_deposit: None,
         total_deposit_limit: None,
+        ..Default::default()
     };
     setup_pool(&mut ctx, &args)?;
 
@@ -147,6 +149,7 @@ fn test_withdraw_multi_user_last_out_winner() -> Result<()> {
         collateral_bps: 500,
         max_deposit: None,
         total_deposit_limit: None,
+        ..Default::default()
     };
     setup_pool(&mut ctx, &args)?;
 
@@ -228,6 +231,7 @@ fn test_withdraw_time_based_early_withdrawal() -> Result<()> {
         collateral_bps: 500, // 5%
         max_deposit: None,
         total_deposit_limit: None,
+        ..Default::default()
     };
     setup_pool(&mut ctx, &args)?;
 
@@ -252,16 +256,15 @@ fn test_withdraw_time_based_early_withdrawal() -> Result<()> {
     let user_ata_account = ctx.svm.get_account(&user_ata).unwrap();
     let user_ata = spl_token::state::Account::unpack(&user_ata_account.data).unwrap();
 
-    // Calculate expected amount
-    // User should get back 25% of their collateral based on time elapsed
-    let fee = chicken::actions::bps(deposit_amount, 0).unwrap();
-    let total_collateral = chicken::actions::bps(deposit_amount - fee, 500).unwrap();
-    let collateral_return = total_collateral * 25 / 100; // 25% of collateral
+    // Collateral vests on the linear curve from the deposit slot, 239 of 990 slots here
+    let fee = bps_up(deposit_amount, 10)?;
+    let total_collateral = bps(deposit_amount - fee, 500)?;
+    let vested = PayoutCurve::Linear.evaluate(239, 990);
+    let collateral_return = bps(total_collateral, vested as u16)?;
+    let gross = deposit_amount - fee - total_collateral + collateral_return;
 
-    assert_eq!(
-        user_ata.amount,
-        deposit_amount - fee - total_collateral + collateral_return
-    );
+    assert_eq!(user_ata.amount, gross - bps_up(gross, 10)?);
+    assert_eq!(user_ata.amount, 960);
 
     Ok(())
 }
@@ -279,6 +282,7 @@ fn test_withdraw_time_based_full_duration() -> Result<()> {
         collateral_bps: 500,
         max_deposit: None,
         total_deposit_limit: None,
+        ..Default::default()
     };
     setup_pool(&mut ctx, &args)?;
 
@@ -302,9 +306,15 @@ fn test_withdraw_time_based_full_duration() -> Result<()> {
     let user_ata_account = ctx.svm.get_account(&user_ata).unwrap();
     let user_ata = spl_token::state::Account::unpack(&user_ata_account.data).unwrap();
 
-    // User should get back full amount including collateral
-    let fee = chicken::actions::bps(deposit_amount, 0).unwrap();
-    assert_eq!(user_ata.amount, deposit_amount - fee);
+    // The deposit landed one slot after the start, so 989 of 990 slots have vested
+    let fee = bps_up(deposit_amount, 10)?;
+    let total_collateral = bps(deposit_amount - fee, 500)?;
+    let vested = PayoutCurve::Linear.evaluate(989, 990);
+    let collateral_return = bps(total_collateral, vested as u16)?;
+    let gross = deposit_amount - fee - total_collateral + collateral_return;
+
+    assert_eq!(user_ata.amount, gross - bps_up(gross, 10)?);
+    assert_eq!(user_ata.amount, 997);
 
     Ok(())
 }
@@ -322,6 +332,7 @@ fn test_withdraw_time_based_multiple_users() -> Result<()> {
         collateral_bps: 500,
         max_deposit: None,
         total_deposit_limit: None,
+        ..Default::default()
     };
     setup_pool(&mut ctx, &args)?;
 
@@ -378,25 +389,38 @@ fn test_withdraw_time_based_multiple_users() -> Result<()> {
     let user3_ata = spl_token::state::Account::unpack(&user3_ata_account.data).unwrap();
 
     // Calculate expected amounts
-    let fee = chicken::actions::bps(deposit_amount, 0).unwrap();
-    let total_collateral = chicken::actions::bps(deposit_amount - fee, 500).unwrap();
-
-    // User 1 should get 25% of collateral
-    let user1_collateral_return = total_collateral * 25 / 100;
-    assert_eq!(
-        user1_ata.amount,
-        deposit_amount - fee - total_collateral + user1_collateral_return
-    );
-
-    // User 2 should get 75% of collateral
-    let user2_collateral_return = total_collateral * 75 / 100;
-    assert_eq!(
-        user2_ata.amount,
-        deposit_amount - fee - total_collateral + user2_collateral_return
-    );
-
-    // User 3 should get full amount back
-    assert_eq!(user3_ata.amount, deposit_amount - fee);
+    let fee = bps_up(deposit_amount, 10)?;
+    let total_collateral = bps(deposit_amount - fee, 500)?;
+    let principal = deposit_amount - fee - total_collateral;
+
+    // User 1 vests 239 of 990 slots and nothing has been forfeited yet
+    let user1_return = bps(
+        total_collateral,
+        PayoutCurve::Linear.evaluate(239, 990) as u16,
+    )?;
+    let user1_gross = principal + user1_return;
+    assert_eq!(user1_ata.amount, user1_gross - bps_up(user1_gross, 10)?);
+    assert_eq!(user1_ata.amount, 960);
+
+    // User 2 vests 739 slots of its own collateral and of its half of what user 1 left
+    let vested = PayoutCurve::Linear.evaluate(739, 990) as u16;
+    let forfeited = total_collateral - user1_return;
+    let user2_return = bps(total_collateral, vested)?
+        + bps(
+            forfeited * total_collateral / (total_collateral * 2),
+            vested,
+        )?;
+    let user2_gross = principal + user2_return;
+    assert_eq!(user2_ata.amount, user2_gross - bps_up(user2_gross, 10)?);
+    assert_eq!(user2_ata.amount, 999);
+
+    // User 3 vests 989 slots and the unvested remainder goes to the protocol
+    let vested = PayoutCurve::Linear.evaluate(989, 990) as u16;
+    let forfeited = total_collateral * 2 - user1_return - user2_return;
+    let user3_return = bps(total_collateral, vested)? + bps(forfeited, vested)?;
+    let user3_gross = principal + user3_return;
+    assert_eq!(user3_ata.amount, user3_gross - bps_up(user3_gross, 10)?);
+    assert_eq!(user3_ata.amount, 1032);
 
     Ok(())
 }
@@ -414,6 +438,7 @@ fn test_withdraw_time_based_exact_halfway() -> Result<()> {
         collateral_bps: 500,
         max_deposit: None,
         total_deposit_limit: None,
+        ..Default::default()
     };
     setup_pool(&mut ctx, &args)?;
 
@@ -437,15 +462,68 @@ fn test_withdraw_time_based_exact_halfway() -> Result<()> {
     let user_ata_account = ctx.svm.get_account(&user_ata).unwrap();
     let user_ata = spl_token::state::Account::unpack(&user_ata_account.data).unwrap();
 
-    // Calculate expected amount (should get exactly 50% of collateral back)
-    let fee = chicken::actions::bps(deposit_amount, 0).unwrap();
-    let total_collateral = chicken::actions::bps(deposit_amount - fee, 500).unwrap();
-    let collateral_return = total_collateral * 50 / 100;
+    // 494 of 990 slots held since the deposit, just short of half the collateral
+    let fee = bps_up(deposit_amount, 10)?;
+    let total_collateral = bps(deposit_amount - fee, 500)?;
+    let vested = PayoutCurve::Linear.evaluate(494, 990);
+    let collateral_return = bps(total_collateral, vested as u16)?;
+    let gross = deposit_amount - fee - total_collateral + collateral_return;
 
-    assert_eq!(
-        user_ata.amount,
-        deposit_amount - fee - total_collateral + collateral_return
-    );
+    assert_eq!(user_ata.amount, gross - bps_up(gross, 10)?);
+    assert_eq!(user_ata.amount, 973);
+
+    Ok(())
+}
+
+#[test_log::test]
+fn test_withdraw_below_min_out() -> Result<()> {
+    let mut ctx = setup_test_context()?;
+    let current_clock = ctx.svm.get_sysvar::<solana_program::clock::Clock>().slot;
+    let args = InitializePoolArgs {
+        pool_id: [0; 16],
+        pool_mode: PoolMode::LastOutWinner,
+        start_time: current_clock + 10,
+        end_time: current_clock + 1000,
+        minimum_deposit: 0,
+        collateral_bps: 500,
+        max_deposit: None,
+        total_deposit_limit: None,
+        ..Default::default()
+    };
+    setup_pool(&mut ctx, &args)?;
 
+    ctx.svm.warp_to_slot(current_clock + 11);
+    let deposit_amount = 1_000_000;
+    let (user1, _) = setup_user(&mut ctx, deposit_amount)?;
+    let (user2, _) = setup_user(&mut ctx, deposit_amount)?;
+    deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user1,
+        deposit_amount,
+    )?;
+    deposit(
+        &mut ctx.svm,
+        &ctx.mint,
+        &ctx.pool_key,
+        &user2,
+        deposit_amount,
+    )?;
+
+    // user1 expected to be last out, but user2 is still in the pool
+    ctx.svm.warp_to_slot(current_clock + 500);
+    let result = withdraw_with_min_out(
+        &mut ctx.svm,
+        &ctx.pool_key,
+        &ctx.mint,
+        &user1,
+        deposit_amount,
+    );
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Payout is below the minimum accepted"));
+    withdraw_with_min_out(&mut ctx.svm, &ctx.pool_key, &ctx.mint, &user1, 900_000)?;
     Ok(())
 }